@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+use crate::layout::Rect;
+use crate::text;
+
+/// A comment on the canvas, saved with the graph and never evaluated.
+#[derive(Clone)]
+#[derive(Serialize, Deserialize)]
+pub enum Annotation {
+    // a titled rectangle drawn behind the nodes, moving it moves what is inside
+    Frame { title: String, rect: Rect },
+    Note { text: String, x: usize, y: usize },
+}
+impl Annotation {
+    /// The text in the top border, with the id used by the annotation commands.
+    pub fn get_title(&self, id: usize) -> String {
+        match self {
+            Annotation::Frame { title, .. } => format!("{} (frame {})", title, id),
+            Annotation::Note { .. } => format!("note {}", id),
+        }
+    }
+
+    pub fn get_rect(&self, id: usize) -> Rect {
+        match self {
+            Annotation::Frame { rect, .. } => *rect,
+            Annotation::Note { text, x, y } => {
+                let lines: Vec<String> = text.split('\n').map(text::escape_control).collect();
+                let w = lines.iter().map(|x| text::get_width(x)).max().unwrap_or(0);
+                // wide enough for the title in the border
+                let w = w.max(text::get_width(&self.get_title(id)) + 2);
+                Rect::new(*x, *y, w + 2, lines.len() + 2)
+            }
+        }
+    }
+
+    pub fn set_position(&mut self, new_x: usize, new_y: usize) {
+        match self {
+            Annotation::Frame { rect, .. } => {
+                rect.x = new_x;
+                rect.y = new_y;
+            }
+            Annotation::Note { x, y, .. } => {
+                *x = new_x;
+                *y = new_y;
+            }
+        }
+    }
+
+    // where a mouse press grabs it, a frame only by its top border so the nodes inside stay clickable
+    pub fn is_handle(&self, id: usize, x: usize, y: usize) -> bool {
+        let rect = self.get_rect(id);
+        match self {
+            Annotation::Frame { .. } => y == rect.y && x >= rect.x && x < rect.x + rect.w,
+            Annotation::Note { .. } => rect.contains(x, y),
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+// how many down and up passes the crossing reduction makes
+const SWEEPS: usize = 8;
+
+/// Puts `node_ids` into columns for a layered layout of the `edges` (from, to) between them and orders
+/// every column to reduce wire crossings. Sources are in the first column and sinks in the last one.
+pub fn get_layers<T: Copy + Eq + Hash>(node_ids: &[T], edges: &[(T, T)]) -> Vec<Vec<T>> {
+    let index: HashMap<T, usize> = node_ids.iter().enumerate().map(|(i, &x)| (x, i)).collect();
+    let edges: Vec<(usize, usize)> = edges.iter()
+        .filter_map(|(from, to)| Some((*index.get(from)?, *index.get(to)?)))
+        .collect();
+    let layer_of = get_layer_of(node_ids.len(), &edges);
+    let layer_count = layer_of.iter().max().map_or(0, |x| x + 1);
+
+    // wires spanning several columns get a dummy vertex in every column they pass through
+    let mut layer_of = layer_of;
+    let mut short_edges = Vec::new();
+    for &(from, to) in &edges {
+        let mut last = from;
+        for layer in layer_of[from] + 1..layer_of[to] {
+            layer_of.push(layer);
+            short_edges.push((last, layer_of.len() - 1));
+            last = layer_of.len() - 1;
+        }
+        short_edges.push((last, to));
+    }
+
+    let mut layers = vec![Vec::new(); layer_count];
+    for (vertex, &layer) in layer_of.iter().enumerate() {
+        layers[layer].push(vertex);
+    }
+    let mut best = layers.clone();
+    let mut best_crossings = count_crossings(&layers, &short_edges);
+    for _ in 0..SWEEPS {
+        for i in 1..layers.len() {
+            order_by_barycenter(&mut layers, i, i - 1, &short_edges, false);
+        }
+        for i in (0..layers.len().saturating_sub(1)).rev() {
+            order_by_barycenter(&mut layers, i, i + 1, &short_edges, true);
+        }
+        let crossings = count_crossings(&layers, &short_edges);
+        if crossings < best_crossings {
+            best = layers.clone();
+            best_crossings = crossings;
+        }
+    }
+    best.into_iter()
+        .map(|layer| layer.into_iter().filter(|&x| x < node_ids.len()).map(|x| node_ids[x]).collect())
+        .collect()
+}
+
+// the longest path from a source for every vertex, then sinks are moved to the last column
+fn get_layer_of(count: usize, edges: &[(usize, usize)]) -> Vec<usize> {
+    let mut layer_of = vec![0; count];
+    let mut incoming = vec![0; count];
+    for &(_, to) in edges {
+        incoming[to] += 1;
+    }
+    let mut queue: Vec<usize> = (0..count).filter(|&x| incoming[x] == 0).collect();
+    while let Some(vertex) = queue.pop() {
+        for &(from, to) in edges.iter().filter(|(from, _)| *from == vertex) {
+            layer_of[to] = layer_of[to].max(layer_of[from] + 1);
+            incoming[to] -= 1;
+            if incoming[to] == 0 {
+                queue.push(to);
+            }
+        }
+    }
+    let last = layer_of.iter().copied().max().unwrap_or(0);
+    for (vertex, layer) in layer_of.iter_mut().enumerate() {
+        let is_sink = edges.iter().any(|x| x.1 == vertex) && !edges.iter().any(|x| x.0 == vertex);
+        if is_sink {
+            *layer = last;
+        }
+    }
+    layer_of
+}
+
+// sorts column `i` by the mean position of each vertex's neighbours in column `fixed`
+fn order_by_barycenter(layers: &mut [Vec<usize>], i: usize, fixed: usize, edges: &[(usize, usize)], upward: bool) {
+    let position: HashMap<usize, usize> = layers[fixed].iter().enumerate().map(|(i, &x)| (x, i)).collect();
+    let mut keyed: Vec<(f64, usize)> = layers[i].iter().enumerate().map(|(current, &vertex)| {
+        let neighbours: Vec<usize> = edges.iter()
+            .filter_map(|&(from, to)| if upward { (from == vertex).then_some(to) } else { (to == vertex).then_some(from) })
+            .filter_map(|x| position.get(&x).copied())
+            .collect();
+        // vertices without neighbours keep their place
+        match neighbours.is_empty() {
+            true => (current as f64, vertex),
+            false => (neighbours.iter().sum::<usize>() as f64 / neighbours.len() as f64, vertex),
+        }
+    }).collect();
+    keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+    layers[i] = keyed.into_iter().map(|(_, vertex)| vertex).collect();
+}
+
+fn count_crossings(layers: &[Vec<usize>], edges: &[(usize, usize)]) -> usize {
+    // the column and the place in it of every vertex
+    let position: HashMap<usize, (usize, usize)> = layers.iter().enumerate()
+        .flat_map(|(layer, vertices)| vertices.iter().enumerate().map(move |(i, &x)| (x, (layer, i))))
+        .collect();
+    let mut crossings = 0;
+    for (i, &(from_a, to_a)) in edges.iter().enumerate() {
+        for &(from_b, to_b) in &edges[i + 1..] {
+            let (from_a, to_a, from_b, to_b) = (position[&from_a], position[&to_a], position[&from_b], position[&to_b]);
+            // every short edge joins neighbouring columns, so edges leaving the same column cross when their ends swap
+            if from_a.0 == from_b.0 && ((from_a.1 < from_b.1 && to_a.1 > to_b.1) || (from_a.1 > from_b.1 && to_a.1 < to_b.1)) {
+                crossings += 1;
+            }
+        }
+    }
+    crossings
+}
@@ -0,0 +1,65 @@
+use std::io::{self, Write};
+use terminal_size::{Width, Height, terminal_size};
+use crate::color::ColorMode;
+use crate::terminal;
+
+/// Where the editor draws to, positions are 1-based columns and rows like the terminal's.
+pub trait Backend {
+    fn get_size(&self) -> Option<(usize, usize)>;
+    fn get_color_mode(&self) -> ColorMode;
+    fn set_color_mode(&mut self, color_mode: ColorMode);
+    fn clear(&mut self);
+    fn clear_line(&mut self, y: usize);
+    // `text` has no control characters, `color` is ignored when colour is off
+    fn put(&mut self, x: usize, y: usize, text: &str, color: Option<[u8; 3]>);
+    fn set_cursor(&mut self, x: usize, y: usize);
+    fn flush(&mut self);
+}
+
+/// Draws to stdout with ANSI escape sequences.
+pub struct TerminalBackend {
+    color_mode: ColorMode,
+}
+impl TerminalBackend {
+    pub fn new() -> Self {
+        Self {
+            color_mode: ColorMode::detect(),
+        }
+    }
+}
+impl Backend for TerminalBackend {
+    fn get_size(&self) -> Option<(usize, usize)> {
+        terminal_size().map(|(Width(w), Height(h))| (w as usize, h as usize))
+    }
+
+    fn get_color_mode(&self) -> ColorMode {
+        self.color_mode
+    }
+
+    fn set_color_mode(&mut self, color_mode: ColorMode) {
+        self.color_mode = color_mode;
+    }
+
+    fn clear(&mut self) {
+        print!("{}", terminal::CLEAR_SCREEN);
+    }
+
+    fn clear_line(&mut self, y: usize) {
+        print!("\x1B[{};1H\x1B[2K", y);
+    }
+
+    fn put(&mut self, x: usize, y: usize, text: &str, color: Option<[u8; 3]>) {
+        match color {
+            Some(color) => print!("\x1B[{};{}H{}", y, x, self.color_mode.paint(text, color)),
+            None => print!("\x1B[{};{}H{}", y, x, text),
+        }
+    }
+
+    fn set_cursor(&mut self, x: usize, y: usize) {
+        print!("\x1B[{};{}H", y, x);
+    }
+
+    fn flush(&mut self) {
+        let _ = io::stdout().flush();
+    }
+}
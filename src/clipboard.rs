@@ -0,0 +1,75 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use crate::editor::Node;
+
+/// Copied nodes as TOML text, kept in a file so another editor session can paste them.
+#[derive(Serialize, Deserialize)]
+struct Clipboard {
+    nodes: Vec<Node>,
+}
+
+// in the user's own runtime directory when there is one, otherwise a file named after the user in the temp directory
+#[cfg(all(unix, not(test)))]
+fn get_path() -> PathBuf {
+    match std::env::var_os("XDG_RUNTIME_DIR").filter(|x| !x.is_empty()) {
+        Some(dir) => PathBuf::from(dir).join("asgore_node_editor_clipboard.toml"),
+        None => std::env::temp_dir().join(format!("asgore_node_editor_clipboard_{}.toml", unsafe { libc::getuid() })),
+    }
+}
+
+// the temp directory is the user's own
+#[cfg(all(not(unix), not(test)))]
+fn get_path() -> PathBuf {
+    std::env::temp_dir().join("asgore_node_editor_clipboard.toml")
+}
+
+// the tests copy and paste through their own file and leave the user's clipboard alone
+#[cfg(test)]
+pub fn get_path() -> PathBuf {
+    std::env::temp_dir().join(format!("asgore_node_editor_test_clipboard_{}.toml", std::process::id()))
+}
+
+// opens the clipboard file only when it is a file of this user and not a link, so nobody else can read or plant it
+fn open(write: bool) -> io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.read(!write).write(write).create(write);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{MetadataExt, OpenOptionsExt, PermissionsExt};
+        options.mode(0o600).custom_flags(libc::O_NOFOLLOW);
+        let file = options.open(get_path())?;
+        let metadata = file.metadata()?;
+        if metadata.uid() != unsafe { libc::getuid() } || !metadata.is_file() {
+            return Err(io::Error::from(io::ErrorKind::PermissionDenied));
+        }
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+        Ok(file)
+    }
+    #[cfg(not(unix))]
+    options.open(get_path())
+}
+
+pub fn to_text(nodes: &[Node]) -> Result<String, String> {
+    let clipboard = Clipboard { nodes: nodes.to_vec() };
+    toml::to_string(&clipboard).map_err(|e| format!("the nodes can not be copied: {}", e))
+}
+
+pub fn from_text(text: &str) -> Result<Vec<Node>, String> {
+    let clipboard: Clipboard = toml::from_str(text).map_err(|e| format!("the clipboard is invalid: {}", e.message()))?;
+    Ok(clipboard.nodes)
+}
+
+pub fn copy(nodes: &[Node]) -> Result<(), String> {
+    let text = to_text(nodes)?;
+    // emptied only once it is known to be ours
+    let mut file = open(true).map_err(|_| "the clipboard can not be written.".to_string())?;
+    file.set_len(0).and_then(|_| file.write_all(text.as_bytes())).map_err(|_| "the clipboard can not be written.".to_string())
+}
+
+pub fn paste() -> Result<Vec<Node>, String> {
+    let mut text = String::new();
+    open(false).and_then(|mut x| x.read_to_string(&mut text)).map_err(|_| "the clipboard is empty.".to_string())?;
+    from_text(&text)
+}
@@ -0,0 +1,95 @@
+use std::env;
+use std::io::IsTerminal;
+
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+// xterm defaults for the 16 basic colours, in SGR order (30-37 then 90-97)
+const PALETTE_16: [[u8; 3]; 16] = [
+    [0, 0, 0], [205, 0, 0], [0, 205, 0], [205, 205, 0],
+    [0, 0, 238], [205, 0, 205], [0, 205, 205], [229, 229, 229],
+    [127, 127, 127], [255, 0, 0], [0, 255, 0], [255, 255, 0],
+    [92, 92, 255], [255, 0, 255], [0, 255, 255], [255, 255, 255],
+];
+
+fn distance(a: [u8; 3], b: [u8; 3]) -> u32 {
+    (0..3).map(|i| (a[i] as i32 - b[i] as i32).pow(2) as u32).sum()
+}
+
+fn nearest_256(color: [u8; 3]) -> u8 {
+    let level = |c: u8| (0..6).min_by_key(|&i| (CUBE_LEVELS[i] as i32 - c as i32).abs()).unwrap();
+    let (r, g, b) = (level(color[0]), level(color[1]), level(color[2]));
+    let cube_color = [CUBE_LEVELS[r], CUBE_LEVELS[g], CUBE_LEVELS[b]];
+    let cube_index = 16 + 36 * r + 6 * g + b;
+
+    let average = (color.iter().map(|&c| c as usize).sum::<usize>() / 3) as u8;
+    let gray_step = (average.saturating_sub(8) as usize / 10).min(23);
+    let gray = 8 + 10 * gray_step as u8;
+    if distance(color, [gray, gray, gray]) < distance(color, cube_color) {
+        (232 + gray_step) as u8
+    } else {
+        cube_index as u8
+    }
+}
+
+fn nearest_16(color: [u8; 3]) -> u8 {
+    let index = (0..16).min_by_key(|&i| distance(color, PALETTE_16[i])).unwrap() as u8;
+    if index < 8 { 30 + index } else { 90 + index - 8 }
+}
+
+#[derive(Clone, Copy)]
+#[derive(PartialEq)]
+pub enum ColorMode {
+    TrueColor,
+    Ansi256,
+    Ansi16,
+    NoColor,
+}
+impl Default for ColorMode {
+    fn default() -> Self {
+        Self::detect()
+    }
+}
+impl ColorMode {
+    pub fn detect() -> Self {
+        if env::var_os("NO_COLOR").is_some_and(|x| !x.is_empty()) || !std::io::stdout().is_terminal() {
+            return ColorMode::NoColor;
+        }
+        if let Ok(colorterm) = env::var("COLORTERM") {
+            if colorterm == "truecolor" || colorterm == "24bit" {
+                return ColorMode::TrueColor;
+            }
+        }
+        match env::var("TERM") {
+            Ok(term) if term == "dumb" => ColorMode::NoColor,
+            Ok(term) if term.contains("256color") => ColorMode::Ansi256,
+            Ok(_) => ColorMode::Ansi16,
+            // the windows console does not set TERM but understands truecolor
+            Err(_) if cfg!(target_os = "windows") => ColorMode::TrueColor,
+            Err(_) => ColorMode::Ansi16,
+        }
+    }
+
+    pub fn get_by_name(name: &str) -> Option<ColorMode> {
+        match name {
+            "auto" => Some(Self::detect()),
+            "truecolor" => Some(ColorMode::TrueColor),
+            "256" => Some(ColorMode::Ansi256),
+            "16" => Some(ColorMode::Ansi16),
+            "off" => Some(ColorMode::NoColor),
+            _ => None,
+        }
+    }
+
+    pub fn is_enabled(self) -> bool {
+        self != ColorMode::NoColor
+    }
+
+    pub fn paint(self, text: &str, color: [u8; 3]) -> String {
+        match self {
+            ColorMode::TrueColor => format!("\x1B[38;2;{};{};{}m{}\x1B[0m", color[0], color[1], color[2], text),
+            ColorMode::Ansi256 => format!("\x1B[38;5;{}m{}\x1B[0m", nearest_256(color), text),
+            ColorMode::Ansi16 => format!("\x1B[{}m{}\x1B[0m", nearest_16(color), text),
+            ColorMode::NoColor => text.to_string(),
+        }
+    }
+}
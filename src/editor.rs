@@ -1,12 +1,25 @@
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
 use serde::{Deserialize, Serialize};
 // use bincode;
-use AsgoreCore;
+use std::fmt;
 use std::fs;
-use colored::Colorize;
-use std::io::{self, Write};
+use std::num::ParseIntError;
+use std::str::FromStr;
 use lazy_static::lazy_static;
-use terminal_size::{Width, Height, terminal_size};
+use crate::annotation::Annotation;
+use crate::arrange;
+use crate::backend::{Backend, TerminalBackend};
+use crate::clipboard;
+use crate::color::ColorMode;
+use crate::layout::{Child, Constraint, Container, Direction, Drawable, Rect, Widget, WidgetKind};
+use crate::legacy;
+use crate::output::OutputLog;
+use crate::palette::Palette;
+use crate::router::Router;
+use crate::text;
+use crate::terminal::{Event, Key, Mouse, MouseKind, Terminal};
+use crate::theme::{self, BorderSet, Theme};
 
 #[derive(Copy)]
 #[derive(Clone)]
@@ -15,12 +28,26 @@ use terminal_size::{Width, Height, terminal_size};
 pub enum MyDataType {
     Isize,
     String,
+    // a reroute node's type until its first connection
+    Any,
 }
 impl MyDataType {
-    pub fn get_color(self) -> [u8; 3] {
+    pub fn get_name(&self) -> &'static str {
         match self {
-            MyDataType::Isize => [68, 139, 211],
-            MyDataType::String => [38, 209, 111],
+            MyDataType::Isize => "isize",
+            MyDataType::String => "string",
+            MyDataType::Any => "any",
+        }
+    }
+
+    pub fn is_compatible(&self, other: MyDataType) -> bool {
+        *self == other || *self == MyDataType::Any || other == MyDataType::Any
+    }
+
+    pub fn get_default_value(&self) -> MyValueType {
+        match self {
+            MyDataType::String => MyValueType::String { value: String::default() },
+            _ => MyValueType::Isize { value: isize::default() },
         }
     }
 }
@@ -41,7 +68,7 @@ impl MyValueType {
         }
     }
     
-    pub fn try_to_String(self) -> String {
+    pub fn try_to_string(self) -> String {
         if let MyValueType::String { value } = self {
             value
         } else {
@@ -51,15 +78,15 @@ impl MyValueType {
     
     pub fn parse_value(&self, str_value: &str) -> MyValueType {
         match self {
-            MyValueType::Isize { value } => MyValueType::Isize { value: str_value.parse::<isize>().unwrap_or(0) },
-            MyValueType::String { value } => MyValueType::String { value: AsgoreCore::fix_escape_chars(str_value) },
+            MyValueType::Isize { value: _ } => MyValueType::Isize { value: str_value.parse::<isize>().unwrap_or(0) },
+            MyValueType::String { value: _ } => MyValueType::String { value: AsgoreCore::fix_escape_chars(str_value) },
         }
     }
 
     pub fn get_data_type(&self) -> MyDataType {
         match self {
-            MyValueType::Isize { value } => MyDataType::Isize,
-            MyValueType::String { value } => MyDataType::String,
+            MyValueType::Isize { value: _ } => MyDataType::Isize,
+            MyValueType::String { value: _ } => MyDataType::String,
         }
     }
 
@@ -76,6 +103,8 @@ lazy_static! {
 	static ref NODES_MAP: HashMap<&'static str, MyNodeTemplate> = [
         ("New number", MyNodeTemplate::NewNumber),
         ("Repeat string", MyNodeTemplate::RepeatString),
+        ("Reroute", MyNodeTemplate::Reroute),
+        ("Delay", MyNodeTemplate::Delay),
 	].iter().cloned().collect();
 }
 
@@ -84,9 +113,12 @@ lazy_static! {
 pub enum MyNodeTemplate {
     NewNumber,
     RepeatString,
+    Reroute,
+    // outputs what its input was one step ago, so loops through it are allowed
+    Delay,
 }
 impl MyNodeTemplate {
-    pub fn new(self) -> Node {
+    pub fn create_node(self) -> Node {
         let mut node: Node;
         match self {
             MyNodeTemplate::NewNumber => {
@@ -98,6 +130,19 @@ impl MyNodeTemplate {
                 node.add_input("string", MyValueType::String { value: String::default() });
                 node.add_input("number", MyValueType::Isize { value: isize::default() });
             }
+            MyNodeTemplate::Reroute => {
+                node = Node::new(self, "Reroute", MyDataType::Any);
+                node.add_input("in", MyValueType::Isize { value: isize::default() });
+                node.inputs[0].input_type = MyDataType::Any;
+            }
+            MyNodeTemplate::Delay => {
+                node = Node::new(self, "Delay", MyDataType::Any);
+                node.add_input("in", MyValueType::Isize { value: isize::default() });
+                node.add_input("initial", MyValueType::Isize { value: isize::default() });
+                for input in node.inputs.iter_mut() {
+                    input.input_type = MyDataType::Any;
+                }
+            }
         };
         node
     }
@@ -106,29 +151,61 @@ impl MyNodeTemplate {
         NODES_MAP.get(node_title).cloned()
     }
 
+    pub fn get_category(&self) -> &'static str {
+        match self {
+            MyNodeTemplate::NewNumber => "Number",
+            MyNodeTemplate::RepeatString => "String",
+            MyNodeTemplate::Reroute | MyNodeTemplate::Delay => "Wiring",
+        }
+    }
+
     pub fn get_node_titles() -> Vec<String> {
         NODES_MAP.keys().map(|x| x.to_string()).collect()
     }
 }
 
+/// A node's id, unique in its editor.
+#[derive(Clone, Copy)]
+#[derive(PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[derive(Debug)]
+#[derive(Serialize, Deserialize)]
+pub struct NodeId(pub usize);
+impl fmt::Display for NodeId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+impl FromStr for NodeId {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(NodeId)
+    }
+}
+
 #[derive(Clone)]
 #[derive(Serialize, Deserialize)]
 pub struct Input {
-    connected_id: usize,
+    connected_id: Option<NodeId>,
     input_type: MyDataType,
-    color: [u8; 3],
     label: String,
+    // a label given to this node's input, shown instead of the template's
+    name: Option<String>,
     pub value: MyValueType,
 }
 impl Input {
+    pub fn get_label(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.label)
+    }
+
     pub fn new(label: &str, value: MyValueType) -> Self {
         let input_type = value.get_data_type();
         Self {
-            connected_id: 0,
-            input_type: input_type,
-            color: input_type.get_color(),
+            connected_id: None,
+            input_type,
             label: label.to_string(),
-            value: value,
+            name: None,
+            value,
         }
     }
 }
@@ -136,16 +213,14 @@ impl Input {
 #[derive(Clone)]
 #[derive(Serialize, Deserialize)]
 pub struct Output {
-    connected_ids: Vec<usize>,
+    connected_ids: Vec<NodeId>,
     output_type: MyDataType,
-    color: [u8; 3],
 }
 impl Output {
     pub fn new(output_type: MyDataType) -> Self {
         Self {
             connected_ids: Vec::new(),
-            output_type: output_type,
-            color: output_type.get_color(),
+            output_type,
         }
     }
 }
@@ -153,8 +228,10 @@ impl Output {
 #[derive(Clone)]
 #[derive(Serialize, Deserialize)]
 pub struct Node {
-    id: usize,
+    id: NodeId,
     title: String,
+    // a name given to this node, shown instead of the template's title
+    name: Option<String>,
     node_type: MyNodeTemplate,
     pub inputs: Vec<Input>,
     output: Output,
@@ -165,9 +242,10 @@ pub struct Node {
 impl Node {
     pub fn new(node_type: MyNodeTemplate, title: &str, output_type: MyDataType) -> Self {
         Self {
-            id: 0,
+            id: NodeId(0),
             title: title.to_string(),
-            node_type: node_type,
+            name: None,
+            node_type,
             inputs: Vec::new(),
             output: Output::new(output_type),
             x: 0,
@@ -176,20 +254,187 @@ impl Node {
         }
     }
 
+    pub fn get_name(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.title)
+    }
+
     pub fn add_input(&mut self, label: &str, defaul: MyValueType) {
         self.inputs.push(Input::new(label, defaul));
     }
+
+    pub fn get_display_text(&self, default_value_display_length: usize) -> String {
+        let mut text = " [".to_owned() + &text::escape_control(self.get_name()) + "] ";
+        for input in &self.inputs {
+            text += &("\n ".to_owned() + &text::escape_control(input.get_label()) + &" ".repeat(default_value_display_length+2));
+        }
+        text += &("\n (id: ".to_owned() + &self.id.to_string() + ")");
+        text
+    }
+
+    pub fn is_reroute(&self) -> bool {
+        matches!(self.node_type, MyNodeTemplate::Reroute)
+    }
+
+    // a node of type any takes the type of its first connection
+    pub fn is_any_typed(&self) -> bool {
+        matches!(self.node_type, MyNodeTemplate::Reroute | MyNodeTemplate::Delay)
+    }
+
+    /// Whether input `i` is only read when stepping, so evaluating the node does not depend on it.
+    pub fn is_delayed_input(&self, i: usize) -> bool {
+        matches!(self.node_type, MyNodeTemplate::Delay) && i == 0
+    }
+
+    /// Drops the inputs its template does not have and adds the ones it is missing.
+    pub fn fit_inputs(&mut self) {
+        let template = self.node_type.clone().create_node();
+        self.inputs.truncate(template.inputs.len());
+        self.inputs.extend(template.inputs[self.inputs.len()..].iter().cloned());
+    }
+
+    /// Also gives the inputs and output the types of its template, a reroute or delay keeps the
+    /// type it was given, and resets values of the wrong type.
+    pub fn fit_template(&mut self) {
+        self.fit_inputs();
+        if !self.is_any_typed() {
+            let template = self.node_type.clone().create_node();
+            for (input, template_input) in self.inputs.iter_mut().zip(template.inputs) {
+                input.input_type = template_input.input_type;
+            }
+            self.output.output_type = template.output.output_type;
+        }
+        for input in self.inputs.iter_mut() {
+            if input.input_type != MyDataType::Any && input.value.get_data_type() != input.input_type {
+                input.value = input.input_type.get_default_value();
+            }
+        }
+    }
+
+    // the node box including its borders, a reroute is a single cell
+    pub fn get_rect(&self, default_value_display_length: usize) -> Rect {
+        if self.is_reroute() {
+            return Rect::new(self.x, self.y, 1, 1);
+        }
+        let (w, h) = self.get_size(default_value_display_length);
+        Rect::new(self.x, self.y, w + 2, h + 2)
+    }
+
+    // the cell left of input `i` where its wire ends
+    pub fn get_input_end(&self, i: usize) -> (usize, usize) {
+        match self.is_reroute() {
+            true => (self.x.saturating_sub(1), self.y),
+            false => (self.x.saturating_sub(1), self.y + i + 2),
+        }
+    }
+
+    // the cell right of the output where its wires start
+    pub fn get_output_start(&self, default_value_display_length: usize) -> (usize, usize) {
+        let rect = self.get_rect(default_value_display_length);
+        match self.is_reroute() {
+            true => (self.x + 1, self.y),
+            false => (rect.x + rect.w, self.y + 2),
+        }
+    }
+
+    // inner size of the node box, the borders add one cell on each side
+    pub fn get_size(&self, default_value_display_length: usize) -> (usize, usize) {
+        let text = self.get_display_text(default_value_display_length);
+        let w = text.split("\n").map(text::get_width).max().unwrap_or(0);
+        (w, self.inputs.len() + 2)
+    }
+}
+
+// what the held mouse button does on the canvas
+#[derive(Clone, Copy)]
+enum Drag {
+    // rectangle select from the first corner to the second
+    Select((usize, usize), (usize, usize)),
+    // moving the selection, holds the last mouse position
+    Move((usize, usize)),
+    // moving the annotation with that id, holds the last mouse position
+    Annotation(usize, (usize, usize)),
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct Editor {
-    nodes: HashMap<usize, Node>,
-    outputs_cache: HashMap<usize, MyValueType>,
+    nodes: HashMap<NodeId, Node>,
+    outputs_cache: HashMap<NodeId, MyValueType>,
     current_id: usize,
     auto_save: bool,
+    annotations: BTreeMap<usize, Annotation>,
+    // what every delay node outputs until the next step, its initial input when missing
+    states: HashMap<NodeId, MyValueType>,
+    #[serde(skip)]
+    theme: Theme,
+    #[serde(skip, default = "get_default_backend")]
+    backend: RefCell<Box<dyn Backend>>,
+    #[serde(skip)]
+    clip: Option<Rect>,
+    #[serde(skip)]
+    canvas: Rect,
+    #[serde(skip)]
+    output: OutputLog,
+    // the node shown in the inspector
+    #[serde(skip)]
+    selected: Option<NodeId>,
+    #[serde(skip)]
+    selection: BTreeSet<NodeId>,
+    // how many steps of (2, 1) the next paste is moved from the copied nodes
+    #[serde(skip)]
+    paste_offset: usize,
+    // the snap grid's cell size, 0 when snapping is off
+    #[serde(skip)]
+    grid: usize,
+    #[serde(skip)]
+    mouse: bool,
+    #[serde(skip)]
+    drag: Option<Drag>,
+    #[serde(skip)]
+    last_outputs: HashMap<NodeId, MyValueType>,
+    // nodes evaluated again after every command that changes the graph
+    #[serde(skip)]
+    watched: BTreeSet<NodeId>,
+    #[serde(skip)]
+    palette: Palette,
+    // the file last saved to or loaded from and whether the graph changed since
+    #[serde(skip)]
+    file: Option<String>,
+    #[serde(skip)]
+    dirty: bool,
+    // set by the running command when it changes the graph
+    #[serde(skip)]
+    changed: bool,
+    #[serde(skip)]
+    status: String,
+    #[serde(skip)]
+    command_line: String,
+    #[serde(skip)]
+    command_cursor: usize,
     w: usize,
     h: usize
 }
+// the start of every save file, followed by the format version
+const FORMAT_MAGIC: &[u8] = b"ANE";
+// raise it when the saved layout changes, and keep a way to load the old one
+const FORMAT_VERSION: u8 = 1;
+
+// how far outside the canvas wires are still routed around nodes
+const ROUTE_MARGIN: usize = 16;
+
+// the side panels are left out when the Editor and Output panels would get narrower than this
+const MIN_MAIN_PANEL_WIDTH: usize = 24;
+const INSPECTOR_WIDTH: usize = 32;
+const PALETTE_WIDTH: usize = 24;
+
+// node ids joined by arrows, like 3 → 5 → 1 → 3
+fn format_path(node_ids: &[NodeId]) -> String {
+    node_ids.iter().map(|x| x.to_string()).collect::<Vec<String>>().join(" → ")
+}
+
+fn get_default_backend() -> RefCell<Box<dyn Backend>> {
+    RefCell::new(Box::new(TerminalBackend::new()))
+}
+
 impl Editor {
     pub fn new() -> Self { // w: usize, h: usize
         Self {
@@ -197,34 +442,84 @@ impl Editor {
             outputs_cache: HashMap::new(),
             current_id: 0,
             auto_save: false,
+            annotations: BTreeMap::new(),
+            states: HashMap::new(),
+            theme: Theme::default(),
+            backend: get_default_backend(),
+            clip: None,
+            canvas: Rect::default(),
+            output: OutputLog::default(),
+            selected: None,
+            selection: BTreeSet::new(),
+            paste_offset: 0,
+            grid: 0,
+            mouse: false,
+            drag: None,
+            last_outputs: HashMap::new(),
+            watched: BTreeSet::new(),
+            palette: Palette::default(),
+            file: None,
+            dirty: false,
+            changed: false,
+            status: String::new(),
+            command_line: String::new(),
+            command_cursor: 0,
             w: 0,
             h: 0
         }
     }
 
-    pub fn load(&self, path: &str) -> Self {
-        let binary_data: Vec<u8> = match fs::read(path) {
-            Ok(f) => f,
-            Err(_) => {
-                println!("the file can not be loaded.");
-                Vec::new()
-            },
+    /// Loads a saved graph, files without a format header are read in the layout from before it.
+    /// Every node gets the inputs of its template so evaluating it can not run out of them.
+    pub fn load(path: &str) -> Result<Self, String> {
+        let binary_data = fs::read(path).map_err(|_| "the file can not be loaded.".to_string())?;
+        let corrupted = |_| "the save file is curropted.".to_string();
+        let mut editor: Self = match binary_data.strip_prefix(FORMAT_MAGIC) {
+            Some([version, rest @ ..]) if *version == FORMAT_VERSION => bincode::deserialize(rest).map_err(corrupted)?,
+            Some(_) => return Err("the save file is from a newer version of the editor.".to_string()),
+            None => bincode::deserialize(&binary_data).map(Self::from_legacy).map_err(corrupted)?,
         };
-        if binary_data.is_empty() {
-            return Self::new();
-        }
-        match bincode::deserialize(&binary_data) {
-            Ok(data) => data,
-            Err(_) => {
-                println!("the save file is curropted.");
-                Self::new()
-            },
-        }
+        editor.nodes.values_mut().for_each(Node::fit_inputs);
+        Ok(editor)
+    }
+
+    fn from_legacy(legacy: legacy::Editor) -> Self {
+        let mut editor = Self::new();
+        editor.current_id = legacy.current_id;
+        editor.auto_save = legacy.auto_save;
+        editor.nodes = legacy.nodes.into_values().map(|node| {
+            let inputs = node.inputs.into_iter().map(|input| Input {
+                connected_id: (input.connected_id != 0).then_some(NodeId(input.connected_id)),
+                input_type: input.input_type,
+                label: input.label,
+                name: None,
+                value: input.value,
+            }).collect();
+            let output = Output {
+                connected_ids: node.output.connected_ids.into_iter().map(NodeId).collect(),
+                output_type: node.output.output_type,
+            };
+            let node = Node {
+                id: NodeId(node.id),
+                title: node.title,
+                name: None,
+                node_type: node.node_type,
+                inputs,
+                output,
+                x: node.x,
+                y: node.y,
+                w: node.w,
+            };
+            (node.id, node)
+        }).collect();
+        editor
     }
 
-    pub fn save(&self, path: &str) {
-        let binary_data = bincode::serialize(&self).unwrap();
-        AsgoreCore::try_write_byte_file(path, &binary_data);
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let mut binary_data = FORMAT_MAGIC.to_vec();
+        binary_data.push(FORMAT_VERSION);
+        binary_data.extend(bincode::serialize(&self).unwrap());
+        fs::write(path, binary_data).map_err(|_| format!("the graph can not be saved to {}.", path))
     }
 
     pub fn enable_auto_save(&mut self) {
@@ -235,67 +530,358 @@ impl Editor {
         self.auto_save = false;
     }
 
-    pub fn add_node(&mut self, node: Node) {
+    /// Adds the node under the next id no node has and returns that id.
+    pub fn add_node(&mut self, node: Node) -> NodeId {
         self.current_id += 1;
+        // ids given with `add_node_with_id` are skipped
+        while self.nodes.contains_key(&NodeId(self.current_id)) {
+            self.current_id += 1;
+        }
         let mut node = node;
-        node.id = self.current_id;
+        node.id = NodeId(self.current_id);
         self.nodes.insert(node.id, node);
+        NodeId(self.current_id)
     }
 
-    fn auto_save(&self) {
-        if self.auto_save {
-            self.save("auto_save.ane")
+    /// Adds the node under `node_id`, so scripts can refer to it without knowing which ids are taken.
+    pub fn add_node_with_id(&mut self, node: Node, node_id: NodeId) -> Result<NodeId, String> {
+        if node_id == NodeId(0) {
+            return Err("Node ids start at 1.".to_string());
+        }
+        if self.nodes.contains_key(&node_id) {
+            return Err(format!("The node id {} is already taken.", node_id));
         }
+        let mut node = node;
+        node.id = node_id;
+        self.nodes.insert(node_id, node);
+        self.auto_save();
+        Ok(node_id)
     }
 
-    fn are_types_matched(&self, from_node_id: usize, to_node_id: usize, input_index: usize) -> bool {
-        self.nodes.get(&from_node_id).unwrap().output.output_type == self.nodes.get(&to_node_id).unwrap().inputs[input_index].input_type
+    /// A new node of the palette's choice for `query`, right of the selected node or in the middle of the canvas.
+    fn create_palette_node(&self, query: &str) -> Option<Node> {
+        let template = self.palette.get_choice(query).and_then(|x| MyNodeTemplate::get_node_by_title(&x))?;
+        let mut node = template.create_node();
+        let (w, h) = node.get_size(5);
+        match self.selected.and_then(|x| self.nodes.get(&x)) {
+            Some(selected) => {
+                node.x = selected.x + selected.w + 6;
+                node.y = selected.y;
+            }
+            None => {
+                let (center_x, center_y) = self.canvas.center();
+                node.x = center_x.saturating_sub((w + 2) / 2).max(self.canvas.x);
+                node.y = center_y.saturating_sub((h + 2) / 2).max(self.canvas.y);
+            }
+        }
+        Some(node)
     }
 
-    fn is_safe_from_infinite_loop(&self, from_node_id: usize, to_node_id: usize, passed_nodes_ids: &Vec<usize>) -> bool {
-        let from_node = self.nodes.get(&from_node_id).unwrap();
-        let mut passed_nodes_ids = passed_nodes_ids.clone();
-        passed_nodes_ids.push(to_node_id);
-        for input in &from_node.inputs {
-            if passed_nodes_ids.contains(&from_node_id) ||
-                input.connected_id != 0 && !self.is_safe_from_infinite_loop(input.connected_id, from_node_id, &passed_nodes_ids) {
-                return false
+    // the id of an existing node typed in a command
+    fn get_node_id(&self, text: &str) -> Option<NodeId> {
+        text.parse().ok().filter(|x| self.nodes.contains_key(x))
+    }
+
+    /// Gives the nodes the ids 1, 2, 3 and so on in their current order, rewriting every connection and reference.
+    pub fn renumber(&mut self) {
+        let mut old_ids: Vec<NodeId> = self.nodes.keys().copied().collect();
+        old_ids.sort();
+        let new_ids: HashMap<NodeId, NodeId> = old_ids.iter().enumerate().map(|(i, &x)| (x, NodeId(i + 1))).collect();
+        // ids of missing nodes, which only a damaged file has, are dropped
+        let new_id = |x: &NodeId| new_ids.get(x).copied();
+        self.nodes = std::mem::take(&mut self.nodes).into_values().map(|mut node| {
+            node.id = new_ids[&node.id];
+            for input in node.inputs.iter_mut() {
+                input.connected_id = input.connected_id.as_ref().and_then(new_id);
+            }
+            node.output.connected_ids = node.output.connected_ids.iter().filter_map(new_id).collect();
+            (node.id, node)
+        }).collect();
+        self.last_outputs = std::mem::take(&mut self.last_outputs).into_iter().filter_map(|(id, value)| Some((new_id(&id)?, value))).collect();
+        self.states = std::mem::take(&mut self.states).into_iter().filter_map(|(id, value)| Some((new_id(&id)?, value))).collect();
+        self.outputs_cache.clear();
+        self.selection = self.selection.iter().filter_map(new_id).collect();
+        self.watched = self.watched.iter().filter_map(new_id).collect();
+        self.selected = self.selected.as_ref().and_then(new_id);
+        self.current_id = self.nodes.len();
+        self.auto_save();
+    }
+
+    #[cfg(test)]
+    pub fn set_backend(&mut self, backend: Box<dyn Backend>) {
+        self.backend = RefCell::new(backend);
+    }
+
+    #[cfg(test)]
+    pub fn get_output_lines(&self) -> Vec<String> {
+        self.output.get_untimed_lines()
+    }
+
+    // writes only the input's side of a connection, like a damaged file can have
+    #[cfg(test)]
+    pub fn set_input_connection(&mut self, node_id: NodeId, input_index: usize, from_node_id: Option<NodeId>) {
+        self.nodes.get_mut(&node_id).unwrap().inputs[input_index].connected_id = from_node_id;
+    }
+
+    pub fn set_theme(&mut self, name: &str) {
+        if let Some(theme) = Theme::get_builtin(name) {
+            self.theme = theme;
+            return;
+        }
+        match Theme::load(&(name.to_string() + ".toml")) {
+            Ok(theme) => self.theme = theme,
+            Err(message) => self.output.message(&message),
+        }
+    }
+
+    fn auto_save(&mut self) {
+        if !self.auto_save {
+            return;
+        }
+        if let Err(message) = self.save("auto_save.ane") {
+            self.output.message(&format!("Autosave failed, {}", message));
+        }
+    }
+
+    fn are_types_matched(&self, from_node_id: NodeId, to_node_id: NodeId, input_index: usize) -> bool {
+        self.nodes.get(&from_node_id).unwrap().output.output_type.is_compatible(self.nodes.get(&to_node_id).unwrap().inputs[input_index].input_type)
+    }
+
+    // gives a reroute or delay still of type any the type `data_type`, and every such node of type any wired to it
+    fn fix_reroute_type(&mut self, node_id: NodeId, data_type: MyDataType) {
+        // a neighbour named by a damaged file can be missing
+        let Some(node) = self.nodes.get_mut(&node_id) else {
+            return;
+        };
+        if !node.is_any_typed() || node.output.output_type != MyDataType::Any || data_type == MyDataType::Any {
+            return;
+        }
+        node.output.output_type = data_type;
+        for input in node.inputs.iter_mut() {
+            input.input_type = data_type;
+            input.value = data_type.get_default_value();
+        }
+        let mut neighbours = node.output.connected_ids.clone();
+        neighbours.extend(node.inputs.iter().filter_map(|x| x.connected_id));
+        for neighbour_id in neighbours {
+            self.fix_reroute_type(neighbour_id, data_type);
+        }
+    }
+
+    // the nodes whose outputs evaluating `node_id` reads, a delay's input is only read when stepping
+    fn get_sources(&self, node_id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        let node = &self.nodes[&node_id];
+        node.inputs.iter().enumerate()
+            .filter(|(i, _)| !node.is_delayed_input(*i))
+            .filter_map(|(_, x)| x.connected_id)
+            .filter(|x| self.nodes.contains_key(x))
+    }
+
+    // the path from `target` down to `start` in the direction data flows, found by a breadth first
+    // search up from `start`'s inputs, so it is the shortest one
+    fn find_upstream(&self, start: NodeId, target: NodeId) -> Option<Vec<NodeId>> {
+        // the node each visited node was reached from, which its output feeds
+        let mut reached_from = HashMap::new();
+        let mut queue = VecDeque::from([start]);
+        while let Some(node_id) = queue.pop_front() {
+            for source in self.get_sources(node_id) {
+                if reached_from.contains_key(&source) {
+                    continue;
+                }
+                reached_from.insert(source, node_id);
+                if source == target {
+                    let mut path = vec![target];
+                    while path.len() == 1 || *path.last().unwrap() != start {
+                        path.push(reached_from[path.last().unwrap()]);
+                    }
+                    return Some(path);
+                }
+                queue.push_back(source);
+            }
+        }
+        None
+    }
+
+    /// The cycle connecting `from_node_id` to input `input_index` of `to_node_id` would make, in the
+    /// direction data flows and ending where it starts.
+    pub fn get_cycle_path(&self, from_node_id: NodeId, to_node_id: NodeId, input_index: usize) -> Option<Vec<NodeId>> {
+        if self.nodes[&to_node_id].is_delayed_input(input_index) {
+            return None;
+        }
+        if from_node_id == to_node_id {
+            return Some(vec![to_node_id, to_node_id]);
+        }
+        let mut path = self.find_upstream(from_node_id, to_node_id)?;
+        path.push(to_node_id);
+        Some(path)
+    }
+
+    // depth first up the inputs, `path` holds the nodes being visited, each fed by the one after it
+    fn find_cycle(&self, node_id: NodeId, path: &mut Vec<NodeId>, visited: &mut HashSet<NodeId>) -> Option<Vec<NodeId>> {
+        if let Some(start) = path.iter().position(|&x| x == node_id) {
+            let mut cycle = vec![node_id];
+            cycle.extend(path[start..].iter().rev());
+            return Some(cycle);
+        }
+        if !visited.insert(node_id) {
+            return None;
+        }
+        path.push(node_id);
+        let sources: Vec<NodeId> = self.get_sources(node_id).collect();
+        for source in sources {
+            if let Some(cycle) = self.find_cycle(source, path, visited) {
+                return Some(cycle);
             }
         }
-        true
+        path.pop();
+        None
     }
 
-    pub fn connect_nodes(&mut self, from_node_id: usize, to_node_id: usize, input_index: usize) {
-        if self.is_safe_from_infinite_loop(from_node_id, to_node_id, &Vec::new()) && self.are_types_matched(from_node_id, to_node_id, input_index) {
-            self.nodes.get_mut(&to_node_id).unwrap().inputs[input_index].connected_id = from_node_id;
+    /// A cycle that evaluating the node would loop through forever.
+    pub fn get_cycle_before(&self, node_id: NodeId) -> Option<Vec<NodeId>> {
+        self.find_cycle(node_id, &mut Vec::new(), &mut HashSet::new())
+    }
+
+    /// The shortest cycle through every node that is on one, each starting at its smallest id.
+    pub fn get_cycles(&self) -> Vec<Vec<NodeId>> {
+        let mut node_ids: Vec<NodeId> = self.nodes.keys().copied().collect();
+        node_ids.sort();
+        let mut cycles = Vec::new();
+        for node_id in node_ids {
+            if let Some(mut cycle) = self.find_upstream(node_id, node_id) {
+                cycle.pop();
+                let start = cycle.iter().enumerate().min_by_key(|(_, x)| **x).map_or(0, |(i, _)| i);
+                cycle.rotate_left(start);
+                cycle.push(cycle[0]);
+                if !cycles.contains(&cycle) {
+                    cycles.push(cycle);
+                }
+            }
+        }
+        cycles
+    }
+
+    pub fn connect_nodes(&mut self, from_node_id: NodeId, to_node_id: NodeId, input_index: usize) {
+        if self.get_cycle_path(from_node_id, to_node_id, input_index).is_none() && self.are_types_matched(from_node_id, to_node_id, input_index) {
+            self.nodes.get_mut(&to_node_id).unwrap().inputs[input_index].connected_id = Some(from_node_id);
             self.nodes.get_mut(&from_node_id).unwrap().output.connected_ids.push(to_node_id);
+            let from_type = self.nodes[&from_node_id].output.output_type;
+            let to_type = self.nodes[&to_node_id].inputs[input_index].input_type;
+            self.fix_reroute_type(to_node_id, from_type);
+            self.fix_reroute_type(from_node_id, to_type);
         }
         self.auto_save();
     }
 
-    pub fn remove_connection(&mut self, to_node_id: usize, input_index: usize) {
-        self.nodes.get_mut(&to_node_id).unwrap().inputs[input_index].connected_id = 0;
-        let from_node = self.nodes.get_mut(&to_node_id).unwrap();
-        if let Some(index) = from_node.output.connected_ids.iter().position(|&x| x == to_node_id) {
-            from_node.output.connected_ids.remove(index);
+    pub fn remove_connection(&mut self, to_node_id: NodeId, input_index: usize) {
+        let from_node_id = self.nodes.get_mut(&to_node_id).unwrap().inputs[input_index].connected_id.take();
+        if let Some(from_node) = from_node_id.and_then(|x| self.nodes.get_mut(&x)) {
+            if let Some(index) = from_node.output.connected_ids.iter().position(|&x| x == to_node_id) {
+                from_node.output.connected_ids.remove(index);
+            }
         }
         self.auto_save();
     }
 
-    pub fn disconnect_node_to_remove(&mut self, node_id: usize) {
+    /// Problems in the graph that would break drawing or evaluating it, one line each.
+    pub fn check(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let mut node_ids: Vec<&NodeId> = self.nodes.keys().collect();
+        node_ids.sort();
+        for &node_id in &node_ids {
+            let node = &self.nodes[node_id];
+            if node.id != *node_id {
+                problems.push(format!("node {} is stored under id {}.", node.id, node_id));
+            }
+            let input_count = node.node_type.clone().create_node().inputs.len();
+            if node.inputs.len() != input_count {
+                problems.push(format!("node {} has {} inputs instead of {}.", node_id, node.inputs.len(), input_count));
+            }
+            for (i, input) in node.inputs.iter().enumerate() {
+                if input.input_type != MyDataType::Any && input.value.get_data_type() != input.input_type {
+                    problems.push(format!("node {} input {} holds a value of the wrong type.", node_id, i));
+                }
+                let Some(from_node_id) = input.connected_id else { continue };
+                match self.nodes.get(&from_node_id) {
+                    None => problems.push(format!("node {} input {} is connected to missing node {}.", node_id, i, from_node_id)),
+                    Some(from_node) if !from_node.output.output_type.is_compatible(input.input_type) => problems.push(format!(
+                        "node {} input {} takes {} but node {} gives {}.",
+                        node_id, i, input.input_type.get_name(), from_node_id, from_node.output.output_type.get_name()
+                    )),
+                    Some(_) => {}
+                }
+            }
+            // every connected input has exactly one entry in its source's output
+            let mut targets = node.output.connected_ids.clone();
+            targets.sort();
+            targets.dedup();
+            for to_node_id in targets {
+                let listed = node.output.connected_ids.iter().filter(|&&x| x == to_node_id).count();
+                let connected = self.nodes.get(&to_node_id).map_or(0, |x| x.inputs.iter().filter(|x| x.connected_id == Some(*node_id)).count());
+                if listed != connected {
+                    problems.push(format!("node {} lists {} connections to node {} but {} inputs are connected.", node_id, listed, to_node_id, connected));
+                }
+            }
+            for &to_node_id in &node_ids {
+                let connected = self.nodes[to_node_id].inputs.iter().any(|x| x.connected_id == Some(*node_id));
+                if connected && !node.output.connected_ids.contains(to_node_id) {
+                    problems.push(format!("node {} does not list its connection to node {}.", node_id, to_node_id));
+                }
+            }
+        }
+        problems
+    }
+
+    /// Fixes what `check` finds and returns those problems. Inputs decide which connections exist,
+    /// the ones from missing nodes or of the wrong type are dropped.
+    pub fn repair(&mut self) -> Vec<String> {
+        let problems = self.check();
+        if problems.is_empty() {
+            return problems;
+        }
+        self.nodes = std::mem::take(&mut self.nodes).into_iter().map(|(node_id, mut node)| {
+            node.id = node_id;
+            node.fit_template();
+            (node_id, node)
+        }).collect();
+        let output_types: HashMap<NodeId, MyDataType> = self.nodes.iter().map(|(id, x)| (*id, x.output.output_type)).collect();
+        for node in self.nodes.values_mut() {
+            node.output.connected_ids.clear();
+            for input in node.inputs.iter_mut() {
+                let output_type = input.connected_id.and_then(|x| output_types.get(&x));
+                if !output_type.is_some_and(|x| x.is_compatible(input.input_type)) {
+                    input.connected_id = None;
+                }
+            }
+        }
+        let mut connections: Vec<(NodeId, NodeId)> = self.nodes.values()
+            .flat_map(|to_node| to_node.inputs.iter().filter_map(|x| x.connected_id.map(|from_node_id| (from_node_id, to_node.id))))
+            .collect();
+        connections.sort();
+        for (from_node_id, to_node_id) in connections {
+            self.nodes.get_mut(&from_node_id).unwrap().output.connected_ids.push(to_node_id);
+        }
+        self.outputs_cache.clear();
+        self.auto_save();
+        problems
+    }
+
+    pub fn disconnect_node_to_remove(&mut self, node_id: NodeId) {
         let node = self.nodes.get(&node_id).unwrap();
         for from_node_input in node.inputs.clone() {
-            if from_node_input.connected_id != 0 {
-                let from_node = self.nodes.get_mut(&from_node_input.connected_id).unwrap();
+            // a damaged file can name nodes that are gone
+            if let Some(from_node) = from_node_input.connected_id.and_then(|x| self.nodes.get_mut(&x)) {
                 from_node.output.connected_ids.retain(|&x| x != node_id);
             }
         }
         let node = self.nodes.get(&node_id).unwrap();
         for to_node_id in node.output.connected_ids.clone() {
-            let to_node = self.nodes.get_mut(&to_node_id).unwrap();
+            let Some(to_node) = self.nodes.get_mut(&to_node_id) else {
+                continue;
+            };
             for i in 0..to_node.inputs.len() {
-                if to_node.inputs[i].connected_id == node_id {
-                    to_node.inputs[i].connected_id = 0;
+                if to_node.inputs[i].connected_id == Some(node_id) {
+                    to_node.inputs[i].connected_id = None;
                     break;
                 }
             }
@@ -303,47 +889,472 @@ impl Editor {
         self.auto_save();
     }
 
-    pub fn remove_node(&mut self, node_id: usize) {
+    pub fn remove_node(&mut self, node_id: NodeId) {
         self.disconnect_node_to_remove(node_id);
         self.nodes.remove(&node_id);
+        self.last_outputs.remove(&node_id);
+        self.states.remove(&node_id);
+        self.watched.remove(&node_id);
+        self.selection.remove(&node_id);
+        if self.selected == Some(node_id) {
+            self.selected = None;
+        }
         // self.auto_save();
     }
 
-    pub fn evaluate_node(&mut self, node_id: usize) -> MyValueType {
+    /// Replaces the selection, its first node is the one shown in the inspector.
+    pub fn set_selection(&mut self, node_ids: &[NodeId]) {
+        self.selection = node_ids.iter().copied().filter(|x| self.nodes.contains_key(x)).collect();
+        self.selected = self.selection.first().copied();
+    }
+
+    pub fn get_nodes_in(&self, rect: Rect) -> Vec<NodeId> {
+        let mut node_ids: Vec<NodeId> = self.nodes.values().filter(|x| x.get_rect(5).intersects(&rect)).map(|x| x.id).collect();
+        node_ids.sort();
+        node_ids
+    }
+
+    pub fn get_node_at(&self, x: usize, y: usize) -> Option<NodeId> {
+        self.nodes.values().filter(|node| node.get_rect(5).contains(x, y)).map(|x| x.id).max()
+    }
+
+    // the smallest rectangle holding every selected node's box
+    pub fn get_selection_rect(&self) -> Option<Rect> {
+        self.selection.iter().map(|x| self.nodes[x].get_rect(5)).reduce(|a, b| {
+            let (right, bottom) = ((a.x + a.w).max(b.x + b.w), (a.y + a.h).max(b.y + b.h));
+            let (x, y) = (a.x.min(b.x), a.y.min(b.y));
+            Rect::new(x, y, right - x, bottom - y)
+        })
+    }
+
+    pub fn move_selection(&mut self, dx: isize, dy: isize) {
+        for node_id in &self.selection {
+            let node = self.nodes.get_mut(node_id).unwrap();
+            node.x = node.x.saturating_add_signed(dx).max(1);
+            node.y = node.y.saturating_add_signed(dy).max(1);
+        }
+    }
+
+    /// Adds the annotation under the next free id and returns that id.
+    pub fn add_annotation(&mut self, annotation: Annotation) -> usize {
+        let id = self.annotations.keys().max().map_or(1, |x| x + 1);
+        self.annotations.insert(id, annotation);
+        self.auto_save();
+        id
+    }
+
+    /// Puts the annotation's top left corner at `x`, `y`, a frame takes the nodes and notes inside it along.
+    pub fn move_annotation(&mut self, id: usize, x: usize, y: usize) {
+        let Some(annotation) = self.annotations.get(&id) else { return };
+        let rect = annotation.get_rect(id);
+        let (dx, dy) = (x.max(1) as isize - rect.x as isize, y.max(1) as isize - rect.y as isize);
+        if let Annotation::Frame { .. } = annotation {
+            for node in self.nodes.values_mut() {
+                if rect.contains_rect(&node.get_rect(5)) {
+                    node.x = node.x.saturating_add_signed(dx).max(1);
+                    node.y = node.y.saturating_add_signed(dy).max(1);
+                }
+            }
+            for (&note_id, note) in self.annotations.iter_mut() {
+                let note_rect = note.get_rect(note_id);
+                if matches!(note, Annotation::Note { .. }) && rect.contains_rect(&note_rect) {
+                    note.set_position(note_rect.x.saturating_add_signed(dx).max(1), note_rect.y.saturating_add_signed(dy).max(1));
+                }
+            }
+        }
+        self.annotations.get_mut(&id).unwrap().set_position(x.max(1), y.max(1));
+        self.auto_save();
+    }
+
+    pub fn get_annotation_at(&self, x: usize, y: usize) -> Option<usize> {
+        self.annotations.iter().filter(|(id, annotation)| annotation.is_handle(**id, x, y)).map(|(id, _)| *id).max()
+    }
+
+    /// Moves the nodes together so the top left one lands on the snap grid, then down until none overlaps another node.
+    pub fn settle_nodes(&mut self, node_ids: &[NodeId]) {
+        let left = node_ids.iter().map(|x| self.nodes[x].x).min().unwrap_or(1);
+        let top = node_ids.iter().map(|x| self.nodes[x].y).min().unwrap_or(1);
+        // the grid lines are on columns and rows 1, 1 + grid, 1 + 2 * grid and so on
+        let snap = |x: usize| match self.grid {
+            0 => x,
+            grid => 1 + (x.saturating_sub(1) + grid / 2) / grid * grid,
+        };
+        let (dx, dy) = (snap(left) as isize - left as isize, snap(top) as isize - top as isize);
+        let step = self.grid.max(1);
+        for offset in (0..).step_by(step) {
+            let overlaps = node_ids.iter().any(|node_id| {
+                let node = &self.nodes[node_id];
+                let mut rect = node.get_rect(5);
+                rect.x = node.x.saturating_add_signed(dx).max(1);
+                rect.y = node.y.saturating_add_signed(dy).max(1) + offset;
+                self.nodes.values().any(|x| !node_ids.contains(&x.id) && x.get_rect(5).intersects(&rect))
+            });
+            if !overlaps {
+                for node_id in node_ids {
+                    let node = self.nodes.get_mut(node_id).unwrap();
+                    node.x = node.x.saturating_add_signed(dx).max(1);
+                    node.y = node.y.saturating_add_signed(dy).max(1) + offset;
+                }
+                return;
+            }
+        }
+    }
+
+    /// Adds copies of `nodes` moved by `dx`, `dy` under fresh ids, settled below any node they would cover, and returns the new ids.
+    /// Connections between the copies are kept, inputs from other nodes are kept with `keep_external`
+    /// when that node exists here and has the same type, every other connection is dropped.
+    pub fn insert_nodes(&mut self, nodes: Vec<Node>, dx: isize, dy: isize, keep_external: bool) -> Vec<NodeId> {
+        let mut new_ids = HashMap::new();
+        let mut inserted = Vec::new();
+        for mut node in nodes {
+            let old_id = node.id;
+            node.x = node.x.saturating_add_signed(dx).max(1);
+            node.y = node.y.saturating_add_signed(dy).max(1);
+            node.output.connected_ids.clear();
+            // the clipboard is a file anyone can edit
+            node.fit_template();
+            let node_id = self.add_node(node);
+            new_ids.insert(old_id, node_id);
+            inserted.push(node_id);
+        }
+        for &node_id in &inserted {
+            let mut inputs = self.nodes[&node_id].inputs.clone();
+            for input in inputs.iter_mut() {
+                let Some(old_id) = input.connected_id else { continue };
+                input.connected_id = match new_ids.get(&old_id) {
+                    Some(&new_id) => Some(new_id),
+                    // the source must not be one of the copies, ids in another session's clipboard can clash with them
+                    None if keep_external && !inserted.contains(&old_id) => match self.nodes.get(&old_id) {
+                        Some(from_node) if from_node.output.output_type.is_compatible(input.input_type) => Some(old_id),
+                        _ => None,
+                    },
+                    None => None,
+                };
+                if let Some(from_node_id) = input.connected_id {
+                    self.nodes.get_mut(&from_node_id).unwrap().output.connected_ids.push(node_id);
+                }
+            }
+            self.nodes.get_mut(&node_id).unwrap().inputs = inputs;
+        }
+        self.settle_nodes(&inserted);
+        self.auto_save();
+        inserted
+    }
+
+    pub fn duplicate_selection(&mut self) {
+        let nodes = self.selection.iter().map(|x| self.nodes[x].clone()).collect();
+        let new_ids = self.insert_nodes(nodes, 2, 1, false);
+        self.set_selection(&new_ids);
+    }
+
+    /// Puts the selected nodes on the clipboard, returns how many were copied.
+    pub fn copy_selection(&mut self) -> Result<usize, String> {
+        let nodes: Vec<Node> = self.selection.iter().map(|x| self.nodes[x].clone()).collect();
+        clipboard::copy(&nodes)?;
+        self.paste_offset = 1;
+        Ok(nodes.len())
+    }
+
+    pub fn cut_selection(&mut self) -> Result<usize, String> {
+        let count = self.copy_selection()?;
+        self.delete_selection();
+        // the first paste puts the nodes back where they were cut from
+        self.paste_offset = 0;
+        Ok(count)
+    }
+
+    /// Adds the clipboard's nodes a little further down and right on every paste and selects them.
+    pub fn paste(&mut self, keep_external: bool) -> Result<usize, String> {
+        let nodes = clipboard::paste()?;
+        let offset = self.paste_offset as isize;
+        let new_ids = self.insert_nodes(nodes, offset * 2, offset, keep_external);
+        self.paste_offset += 1;
+        self.set_selection(&new_ids);
+        Ok(new_ids.len())
+    }
+
+    pub fn delete_selection(&mut self) {
+        for node_id in std::mem::take(&mut self.selection) {
+            self.remove_node(node_id);
+        }
+        self.auto_save();
+    }
+
+    /// Lines the selected nodes up on the outermost edge of the selection on `side`.
+    pub fn align_selection(&mut self, side: &str) -> Result<(), String> {
+        if !["left", "right", "top", "bottom"].contains(&side) {
+            return Err("Use align left, right, top or bottom.".to_string());
+        }
+        let mut rects: Vec<(NodeId, Rect)> = self.selection.iter().map(|x| (*x, self.nodes[x].get_rect(5))).collect();
+        let (lefts, tops) = (rects.iter().map(|(_, r)| r.x), rects.iter().map(|(_, r)| r.y));
+        let (rights, bottoms) = (rects.iter().map(|(_, r)| r.x + r.w), rects.iter().map(|(_, r)| r.y + r.h));
+        let (left, top) = (lefts.min().unwrap_or(0), tops.min().unwrap_or(0));
+        let (right, bottom) = (rights.max().unwrap_or(0), bottoms.max().unwrap_or(0));
+        // the nodes nearest the side move first, the ones landing on them are settled below
+        rects.sort_by_key(|(_, r)| match side {
+            "left" => r.x - left,
+            "right" => right - (r.x + r.w),
+            "top" => r.y - top,
+            _ => bottom - (r.y + r.h),
+        });
+        for (node_id, rect) in rects {
+            let node = self.nodes.get_mut(&node_id).unwrap();
+            match side {
+                "left" => node.x = left,
+                "right" => node.x = right - rect.w,
+                "top" => node.y = top,
+                _ => node.y = bottom - rect.h,
+            }
+            self.settle_nodes(&[node_id]);
+        }
+        self.auto_save();
+        Ok(())
+    }
+
+    /// Lays the nodes (or only the selected ones) out in columns from sources on the left to sinks on the right,
+    /// starting at the top left corner of the nodes' current bounding box.
+    pub fn arrange(&mut self, selection_only: bool) {
+        let mut node_ids: Vec<NodeId> = match selection_only {
+            true => self.selection.iter().copied().collect(),
+            false => self.nodes.keys().copied().collect(),
+        };
+        node_ids.sort();
+        let mut edges = Vec::new();
+        for node_id in &node_ids {
+            // wires into a delay run back against the flow, leaving them out keeps the columns in order
+            for input in self.nodes[node_id].inputs.iter().enumerate().filter(|(i, _)| !self.nodes[node_id].is_delayed_input(*i)).map(|(_, x)| x) {
+                if let Some(from_node_id) = input.connected_id.filter(|x| node_ids.contains(x)) {
+                    edges.push((from_node_id, *node_id));
+                }
+            }
+        }
+        let left = node_ids.iter().map(|x| self.nodes[x].x).min().unwrap_or(1);
+        let top = node_ids.iter().map(|x| self.nodes[x].y).min().unwrap_or(1);
+        let mut x = left;
+        for layer in arrange::get_layers(&node_ids, &edges) {
+            let mut y = top;
+            let mut w = 0;
+            for node_id in layer {
+                let node = self.nodes.get_mut(&node_id).unwrap();
+                let rect = node.get_rect(5);
+                node.x = x;
+                node.y = y;
+                y += rect.h + 1;
+                w = w.max(rect.w);
+            }
+            // room for the wires between the columns
+            x += w + 6;
+        }
+        // the columns can reach over nodes that were not arranged
+        self.settle_nodes(&node_ids);
+        self.auto_save();
+    }
+
+    /// Spreads the selected nodes between the outermost two so the gaps between their boxes are equal.
+    pub fn distribute_selection(&mut self, direction: &str) -> Result<(), String> {
+        let horizontal = match direction {
+            "h" => true,
+            "v" => false,
+            _ => return Err("Use distribute h or v.".to_string()),
+        };
+        if self.selection.len() < 3 {
+            return Err("Select at least 3 nodes to distribute.".to_string());
+        }
+        // (start, size, node id) of every box along the direction
+        let mut boxes: Vec<(usize, usize, NodeId)> = self.selection.iter().map(|x| {
+            let rect = self.nodes[x].get_rect(5);
+            if horizontal { (rect.x, rect.w, *x) } else { (rect.y, rect.h, *x) }
+        }).collect();
+        boxes.sort();
+        let start = boxes[0].0;
+        let end = boxes.iter().map(|(start, size, _)| start + size).max().unwrap_or(start);
+        let sizes: usize = boxes.iter().map(|(_, size, _)| size).sum();
+        let gap = end.saturating_sub(start + sizes) / (boxes.len() - 1);
+        let mut position = start;
+        for (_, size, node_id) in boxes {
+            let node = self.nodes.get_mut(&node_id).unwrap();
+            if horizontal { node.x = position } else { node.y = position }
+            self.settle_nodes(&[node_id]);
+            position += size + gap;
+        }
+        self.auto_save();
+        Ok(())
+    }
+
+    /// Gives the node a name of its own, an empty one brings back the template's title.
+    pub fn rename_node(&mut self, node_id: NodeId, name: &str) -> Result<(), String> {
+        let node = self.nodes.get_mut(&node_id).ok_or(format!("Unknown node id {}.", node_id))?;
+        node.name = (!name.is_empty()).then(|| name.to_string());
+        self.auto_save();
+        Ok(())
+    }
+
+    /// Gives one input of the node a label of its own, an empty one brings back the template's label.
+    pub fn rename_input(&mut self, node_id: NodeId, input_index: usize, label: &str) -> Result<(), String> {
+        let node = self.nodes.get_mut(&node_id).ok_or(format!("Unknown node id {}.", node_id))?;
+        let input = node.inputs.get_mut(input_index).ok_or(format!("Unknown input index {}.", input_index))?;
+        input.name = (!label.is_empty()).then(|| label.to_string());
+        self.auto_save();
+        Ok(())
+    }
+
+    /// Sets the input called `input` (or at that index) on every selected node that has one, returns how many were set.
+    pub fn set_selection_value(&mut self, input: &str, value: &str) -> usize {
+        let mut count = 0;
+        for node_id in &self.selection {
+            let node = self.nodes.get_mut(node_id).unwrap();
+            let index = match input.parse::<usize>() {
+                Ok(index) if index < node.inputs.len() => Some(index),
+                _ => node.inputs.iter().position(|x| x.get_label() == input || x.label == input),
+            };
+            if let Some(index) = index {
+                node.inputs[index].value = node.inputs[index].value.parse_value(value);
+                count += 1;
+            }
+        }
+        self.auto_save();
+        count
+    }
+
+    pub fn evaluate_node(&mut self, node_id: NodeId) -> MyValueType {
         self.outputs_cache = HashMap::new();
-        self._evaluate_node(node_id)
+        let value = self._evaluate_node(node_id);
+        self.last_outputs.insert(node_id, value.clone());
+        value
     }
 
-    fn _evaluate_node(&self, node_id: usize) -> MyValueType {
+    fn _evaluate_node(&self, node_id: NodeId) -> MyValueType {
         let node = self.nodes.get(&node_id).unwrap();
         match node.node_type {
             MyNodeTemplate::NewNumber => {
-                let _0 = self.evaluate_input(&node.inputs[0]).try_to_isize();
-                MyValueType::Isize { value: _0 }
+                let number = self.evaluate_input(&node.inputs[0]).try_to_isize();
+                MyValueType::Isize { value: number }
             }
             MyNodeTemplate::RepeatString => {
-                let _0 = self.evaluate_input(&node.inputs[0]).try_to_String();
-                let _1 = self.evaluate_input(&node.inputs[1]).try_to_isize();
-                MyValueType::String { value: _0.repeat(_1 as usize) }
+                let string = self.evaluate_input(&node.inputs[0]).try_to_string();
+                let count = self.evaluate_input(&node.inputs[1]).try_to_isize();
+                MyValueType::String { value: string.repeat(count as usize) }
             }
+            // passes its input through unchanged
+            MyNodeTemplate::Reroute => self.evaluate_input(&node.inputs[0]),
+            MyNodeTemplate::Delay => match self.states.get(&node_id) {
+                Some(value) => value.clone(),
+                None => self.evaluate_input(&node.inputs[1]),
+            },
         }
     }
 
-    pub fn evaluate_input(&self, input: &Input) -> MyValueType {
-        if input.connected_id == 0 {
-            input.value.clone()
+    // evaluates the node and logs its value, or why it can not be evaluated
+    fn print_evaluation(&mut self, node_id: NodeId) {
+        match self.get_cycle_before(node_id) {
+            Some(cycle) => self.output.message(&format!("Node {} can not be evaluated, it depends on the cycle {}.", node_id, format_path(&cycle))),
+            None => {
+                let text = self.evaluate_node(node_id).get_print_text();
+                self.output.push(Some(node_id.0), &text);
+            }
         }
-        else {
-            match self.outputs_cache.get(&input.connected_id) {
-                Some(value) => value.clone(),
-                _ => self._evaluate_node(input.connected_id),
+    }
+
+    fn print_watched(&mut self) {
+        for node_id in self.watched.clone() {
+            self.print_evaluation(node_id);
+        }
+    }
+
+    /// The nodes ordered so every node comes after the nodes it reads, the smallest id first when
+    /// several are ready. Nodes on or after a cycle are left out.
+    pub fn get_evaluation_order(&self) -> Vec<NodeId> {
+        let mut waiting: HashMap<NodeId, usize> = self.nodes.keys().map(|&x| (x, 0)).collect();
+        let mut readers: HashMap<NodeId, Vec<NodeId>> = HashMap::new();
+        for &node_id in self.nodes.keys() {
+            for source in self.get_sources(node_id) {
+                *waiting.get_mut(&node_id).unwrap() += 1;
+                readers.entry(source).or_default().push(node_id);
+            }
+        }
+        let mut ready: BTreeSet<NodeId> = waiting.iter().filter(|(_, &x)| x == 0).map(|(&x, _)| x).collect();
+        let mut order = Vec::new();
+        while let Some(node_id) = ready.pop_first() {
+            order.push(node_id);
+            for reader in readers.get(&node_id).into_iter().flatten() {
+                let count = waiting.get_mut(reader).unwrap();
+                *count -= 1;
+                if *count == 0 {
+                    ready.insert(*reader);
+                }
             }
         }
+        order
+    }
+
+    /// Advances the graph one tick: every delay node reads its input, then they all output what they read at once.
+    pub fn step(&mut self) {
+        self.outputs_cache = HashMap::new();
+        let mut delay_ids: Vec<NodeId> = self.nodes.values().filter(|x| matches!(x.node_type, MyNodeTemplate::Delay)).map(|x| x.id).collect();
+        delay_ids.sort();
+        let states: Vec<(NodeId, MyValueType)> = delay_ids.into_iter()
+            .map(|x| (x, self.evaluate_input(&self.nodes[&x].inputs[0])))
+            .collect();
+        self.states.extend(states);
+    }
+
+    /// Everything about the selected node: its inputs with their full values and sources, and its last result.
+    pub fn get_inspector_text(&self) -> String {
+        let node = match self.selected.and_then(|x| self.nodes.get(&x)) {
+            Some(node) => node,
+            None => return "No node selected, use inspect {node id}.".to_string(),
+        };
+        let mut text = format!("[{}] (id: {})", text::escape_control(node.get_name()), node.id);
+        if node.name.is_some() {
+            text += &format!("\ntemplate: {}", node.title);
+        }
+        text += &format!("\noutput: {}", node.output.output_type.get_name());
+        if let Some(value) = self.states.get(&node.id) {
+            text += &format!("\nstate: {}", text::escape_control(&value.get_print_text()));
+        }
+        text += &match self.last_outputs.get(&node.id) {
+            Some(value) => format!("\nlast output: {}", text::escape_control(&value.get_print_text())),
+            None => "\nlast output: not evaluated".to_string(),
+        };
+        for (i, input) in node.inputs.iter().enumerate() {
+            text += &format!("\n\n{} {}: {}", i, text::escape_control(input.get_label()), input.input_type.get_name());
+            match input.connected_id.and_then(|x| self.nodes.get(&x)) {
+                Some(from_node) => text += &format!("\n  from: [{}] (id: {})", text::escape_control(from_node.get_name()), from_node.id),
+                None => text += &format!("\n  value: {}", text::escape_control(&input.value.get_print_text())),
+            }
+        }
+        text
+    }
+
+    pub fn get_status_text(&self) -> String {
+        let file = self.file.as_deref().unwrap_or("untitled");
+        let state = if self.dirty { "modified" } else { "saved" };
+        let autosave = if self.auto_save { "on" } else { "off" };
+        let connections = self.nodes.values().flat_map(|x| &x.inputs).filter(|x| x.connected_id.is_some()).count();
+        let selected = match self.selection.is_empty() {
+            true => "none".to_string(),
+            false => self.selection.iter().map(|x| x.to_string()).collect::<Vec<String>>().join(", "),
+        };
+        format!(
+            "{} ({}) | autosave {} | {} nodes, {} connections | selected: {} | {}",
+            file, state, autosave, self.nodes.len(), connections, selected, text::escape_control(&self.status)
+        )
+    }
+
+    pub fn evaluate_input(&self, input: &Input) -> MyValueType {
+        match input.connected_id.filter(|x| self.nodes.contains_key(x)) {
+            None => input.value.clone(),
+            Some(from_node_id) => match self.outputs_cache.get(&from_node_id) {
+                Some(value) => value.clone(),
+                _ => self._evaluate_node(from_node_id),
+            },
+        }
     }
 }
 
 pub trait UI {
     fn clear_win(&self);
+    fn put(&self, x: usize, y: usize, text: &str, color: Option<[u8; 3]>);
     // fn is_out_of_win(&self, x: usize, y: usize) -> bool;
     fn draw_rect(
         &self,
@@ -351,14 +1362,8 @@ pub trait UI {
         y: usize,
         w: usize,
         h: usize,
-        trcorner: char,
-        tlcorner: char,
-        drcorner: char,
-        dlcorner: char,
-        tedge: char,
-        dedge: char,
-        redge: char,
-        ledge: char
+        border: &BorderSet,
+        color: Option<[u8; 3]>
     );
     fn draw_container(
         &self,
@@ -367,14 +1372,7 @@ pub trait UI {
         y: usize,
         w: usize,
         h: usize,
-        trcorner: char,
-        tlcorner: char,
-        drcorner: char,
-        dlcorner: char,
-        tedge: char,
-        dedge: char,
-        redge: char,
-        ledge: char
+        border: &BorderSet
     );
     fn draw_textarea(&self, text: &str, x: usize, y: usize) -> (usize, usize);
     fn draw_textarea_cut_overflaw(&self, text: &str, x: usize, y: usize, w: usize, h: usize);
@@ -383,92 +1381,61 @@ pub trait UI {
         text: &str,
         x: usize,
         y: usize,
-        trcorner: char,
-        tlcorner: char,
-        drcorner: char,
-        dlcorner: char,
-        tedge: char,
-        dedge: char,
-        redge: char,
-        ledge: char
+        border: &BorderSet,
+        color: Option<[u8; 3]>
     ) -> (usize, usize);
-    fn draw_bordered_textarea_cut_overflaw(
-        &self,
-        text: &str,
-        x: usize,
-        y: usize,
-        w: usize,
-        h: usize,
-        trcorner: char,
-        tlcorner: char,
-        drcorner: char,
-        dlcorner: char,
-        tedge: char,
-        dedge: char,
-        redge: char,
-        ledge: char
-    );
     fn draw_node(
         &mut self,
         node: &mut Node,
-        trcorner: char,
-        tlcorner: char,
-        drcorner: char,
-        dlcorner: char,
-        tedge: char,
-        dedge: char,
-        redge: char,
-        ledge: char,
-        input_socket: char,
-        output_socket: char,
         default_value_display_length: usize
     );
     fn draw_nodes(
         &mut self,
-        trcorner: char,
-        tlcorner: char,
-        drcorner: char,
-        dlcorner: char,
-        tedge: char,
-        dedge: char,
-        redge: char,
-        ledge: char,
-        input_socket: char,
-        output_socket: char,
         default_value_display_length: usize
     );
-    fn draw_connection(
-        &self,
-        from_node_w: usize,
-        to_node_id: usize,
-        input_index: usize,
-        color: [u8; 3],
-        trcorner: char,
-        tlcorner: char,
-        drcorner: char,
-        dlcorner: char,
-        hedge: char,
-        vedge: char
-    );
+    fn draw_connections(&self, default_value_display_length: usize);
+    fn draw_annotations(&self);
+    fn draw_socket(&self, x: usize, y: usize, socket: char, data_type: MyDataType);
     fn run(&mut self);
+    fn update_size(&mut self);
+    fn get_theme(&self) -> &Theme;
+    fn get_screen_rect(&self) -> Rect;
+    fn build_layout(&self) -> Container;
+    fn get_output_rect(&self) -> Rect;
+    fn scroll_output(&mut self, lines: isize);
+    fn draw_widget(&mut self, widget: &Widget, rect: Rect);
+    fn redraw_widget(&mut self, kind: WidgetKind);
     fn draw(&mut self);
-    fn events(&mut self) -> bool;
+    fn draw_prompt(&self);
+    fn edit_command_line(&mut self, key: Key);
+    fn events(&mut self, terminal: &mut Terminal) -> bool;
+    fn mouse_event(&mut self, mouse: Mouse);
+    fn run_command(&mut self, command: &str) -> bool;
+    fn execute_command(&mut self, command: &str) -> bool;
 }
 impl UI for Editor {
-    #[cfg(target_os = "windows")]
     fn clear_win(&self) {
-        std::process::Command::new("cmd")
-            .arg("/c")
-            .arg("cls")
-            .status()
-            .unwrap();
+        self.backend.borrow_mut().clear();
     }
 
-    #[cfg(target_os = "unix")]
-    fn clear_win(&self) {
-        std::process::Command::new("clear")
-            .status()
-            .unwrap();
+    // writes `text` at column `x` of row `y`, anything outside the clip rectangle is dropped
+    fn put(&self, x: usize, y: usize, text: &str, color: Option<[u8; 3]>) {
+        let mut x = x;
+        // callers escape user data so widths stay right, this only guards the terminal
+        let mut text = text::escape_control(text);
+        if let Some(clip) = self.clip {
+            if y < clip.y || y >= clip.y + clip.h {
+                return;
+            }
+            let skip = clip.x.saturating_sub(x);
+            let take = (clip.x + clip.w).saturating_sub(x + skip);
+            text = text::truncate(&text::skip_columns(&text, skip), take);
+            x += skip;
+        }
+        if text.is_empty() {
+            return;
+        }
+        self.backend.borrow_mut().put(x, y, &text, color);
     }
 
     // fn is_out_of_win(&self, x: usize, y: usize) -> bool {
@@ -485,21 +1452,15 @@ impl UI for Editor {
         y: usize,
         w: usize,
         h: usize,
-        trcorner: char,
-        tlcorner: char,
-        drcorner: char,
-        dlcorner: char,
-        tedge: char,
-        dedge: char,
-        redge: char,
-        ledge: char
+        border: &BorderSet,
+        color: Option<[u8; 3]>
     ) {
-        print!("\x1B[{};{}H{}{}{}", y, x, tlcorner, AsgoreCore::repeat_char(tedge, w), trcorner);
+        self.put(x, y, &format!("{}{}{}", border.tlcorner, AsgoreCore::repeat_char(border.tedge, w), border.trcorner), color);
         for i in 1..=h {
-            print!("\x1B[{};{}H{}", y + i, x, ledge);
-            print!("\x1B[{};{}H{}", y + i, x + w + 1, redge);
+            self.put(x, y + i, &border.ledge.to_string(), color);
+            self.put(x + w + 1, y + i, &border.redge.to_string(), color);
         }
-        print!("\x1B[{};{}H{}{}{}", y + h + 1, x, dlcorner, AsgoreCore::repeat_char(dedge, w), drcorner);
+        self.put(x, y + h + 1, &format!("{}{}{}", border.dlcorner, AsgoreCore::repeat_char(border.dedge, w), border.drcorner), color);
     }
 
     fn draw_container(
@@ -509,45 +1470,37 @@ impl UI for Editor {
         y: usize,
         w: usize,
         h: usize,
-        trcorner: char,
-        tlcorner: char,
-        drcorner: char,
-        dlcorner: char,
-        tedge: char,
-        dedge: char,
-        redge: char,
-        ledge: char
+        border: &BorderSet
     ) {
-        self.draw_rect(x, y, w, h, trcorner, tlcorner, drcorner, dlcorner, tedge, dedge, redge, ledge);
-        print!("\x1B[{};{}H{}", y, x+1, (" ".to_owned() + &title + " ").chars().take(w).collect::<String>());
+        self.draw_rect(x, y, w, h, border, None);
+        self.put(x+1, y, &text::truncate(&(" ".to_owned() + &title + " "), w), None);
     }
     
     fn draw_textarea(&self, text: &str, x: usize, y: usize) -> (usize, usize) {
         let lines = text.split("\n").map(|x| x.to_string()).collect::<Vec<String>>();
-        let longest = lines.iter().map(|x| x.chars().count()).max().unwrap_or(0);
+        let longest = lines.iter().map(|x| text::get_width(x)).max().unwrap_or(0);
         if longest == 0 {
             return (0, 0);
         }
         for (i, line) in lines.iter().enumerate() {
-            print!("\x1B[{};{}H{}", y + i, x, line.to_owned() + &" ".repeat(longest - line.len()));
+            self.put(x, y + i, &text::pad(line, longest), None);
         }
         (longest, lines.len())
     }
     
     fn draw_textarea_cut_overflaw(&self, text: &str, x: usize, y: usize, w: usize, h: usize) {
         let lines = text.split("\n").map(|x| x.to_string()).collect::<Vec<String>>();
-        let longest = lines.iter().map(|x| x.chars().count()).max().unwrap_or(0);
+        let longest = lines.iter().map(|x| text::get_width(x)).max().unwrap_or(0);
         if longest == 0 {
             return;
         }
-        for i in 0..std::cmp::min(lines.len(), h) {
-            let line = &lines[i];
-            if line.chars().count() > w {
-                let mut t = line.chars().take(std::cmp::max(w-1, 0)).map(|x| x.to_string()).collect::<String>();
+        for (i, line) in lines.iter().take(h).enumerate() {
+            if text::get_width(line) > w {
+                let mut t = text::truncate(line, w.saturating_sub(1));
                 t += if longest > 1 {"-"} else {""};
-                print!("\x1B[{};{}H{}", y + i, x, t);
+                self.put(x, y + i, &text::pad(&t, w), None);
             } else {
-                print!("\x1B[{};{}H{}", y + i, x, line.to_owned() + &" ".repeat(w - line.len()));
+                self.put(x, y + i, &text::pad(line, w), None);
             }
         }
     }
@@ -557,266 +1510,862 @@ impl UI for Editor {
         text: &str,
         x: usize,
         y: usize,
-        trcorner: char,
-        tlcorner: char,
-        drcorner: char,
-        dlcorner: char,
-        tedge: char,
-        dedge: char,
-        redge: char,
-        ledge: char
+        border: &BorderSet,
+        color: Option<[u8; 3]>
     ) -> (usize, usize) {
         let (w, h) = self.draw_textarea(text, x+1, y+1);
-        self.draw_rect(x, y, w, h, trcorner, tlcorner, drcorner, dlcorner, tedge, dedge, redge, ledge);
+        self.draw_rect(x, y, w, h, border, color);
         (w, h)
     }
-    
-    fn draw_bordered_textarea_cut_overflaw(
-        &self,
-        text: &str,
-        x: usize,
-        y: usize,
-        w: usize,
-        h: usize,
-        trcorner: char,
-        tlcorner: char,
-        drcorner: char,
-        dlcorner: char,
-        tedge: char,
-        dedge: char,
-        redge: char,
-        ledge: char
-    ) {
-        self.draw_textarea_cut_overflaw(text, x+1, y+1, w, h);
-        self.draw_rect(x, y, w, h, trcorner, tlcorner, drcorner, dlcorner, tedge, dedge, redge, ledge);
-    }
 
     fn draw_node(
         &mut self,
         node: &mut Node,
-        trcorner: char,
-        tlcorner: char,
-        drcorner: char,
-        dlcorner: char,
-        tedge: char,
-        dedge: char,
-        redge: char,
-        ledge: char,
-        input_socket: char,
-        output_socket: char,
         default_value_display_length: usize
     ) {
-        let mut text = " [".to_owned() + &node.title + "] ";
-        for input in &node.inputs {
-            text += &("\n ".to_owned() + &input.label + &" ".repeat(default_value_display_length+2));
+        let theme = self.theme.clone();
+        let text = node.get_display_text(default_value_display_length);
+        // selected nodes get the selection colour, or the panel border when colour is off
+        let (border, color) = match self.selection.contains(&node.id) {
+            true if self.backend.borrow().get_color_mode().is_enabled() => (theme.node, Some(theme.selection)),
+            true => (theme.container, None),
+            false => (theme.node, None),
+        };
+        if node.is_reroute() {
+            let color = color.unwrap_or(theme.get_type_color(node.output.output_type));
+            self.put(node.x, node.y, &theme.sockets.reroute.to_string(), Some(color));
+            return;
         }
-        text += &("\n (id: ".to_owned() + &node.id.to_string() + ")");
-        let (w, _) = self.draw_bordered_textarea(&text, node.x, node.y, trcorner, tlcorner, drcorner, dlcorner, tedge, dedge, redge, ledge);
+        let (w, _) = self.draw_bordered_textarea(&text, node.x, node.y, &border, color);
         node.w = w;
         for (i, input) in node.inputs.iter().enumerate() {
-            if input.connected_id == 0 {
-                self.draw_textarea_cut_overflaw(&input.value.get_print_text(), node.x+w-default_value_display_length, node.y+2+i, default_value_display_length, 1);
+            if input.connected_id.is_none() {
+                self.draw_textarea_cut_overflaw(&text::escape_control(&input.value.get_print_text()), node.x+w-default_value_display_length, node.y+2+i, default_value_display_length, 1);
             }
         }
         for (i, input) in node.inputs.iter().enumerate() {
-            let input_color = input.color;
-            print!("\x1B[{};{}H{}", node.y + 2 + i, node.x, input_socket.to_string().truecolor(input_color[0], input_color[1], input_color[2]));
-            if node.inputs[i].connected_id != 0 {
-                self.draw_connection(w, node.id, i, input_color, '┐', '┌', '┘', '└', '─', '│');
-            }
+            self.draw_socket(node.x, node.y + 2 + i, theme.sockets.input, input.input_type);
         }
-        let output_color = node.output.color;
-        print!("\x1B[{};{}H{}", node.y + 2, node.x + w + 1, output_socket.to_string().truecolor(output_color[0], output_color[1], output_color[2]));
+        self.draw_socket(node.x + w + 1, node.y + 2, theme.sockets.output, node.output.output_type);
     }
     
     fn draw_nodes(
         &mut self,
-        trcorner: char,
-        tlcorner: char,
-        drcorner: char,
-        dlcorner: char,
-        tedge: char,
-        dedge: char,
-        redge: char,
-        ledge: char,
-        input_socket: char,
-        output_socket: char,
         default_value_display_length: usize
     ) {
-        for (_,  mut node) in self.nodes.clone() {
-            self.draw_node(&mut node, trcorner, tlcorner, drcorner, dlcorner, tedge, dedge, redge, ledge, input_socket, output_socket, default_value_display_length);
+        // in id order, so newer nodes are drawn over older ones like `get_node_at` picks them
+        let mut nodes: Vec<Node> = self.nodes.values().cloned().collect();
+        nodes.sort_by_key(|x| x.id);
+        for mut node in nodes {
+            self.draw_node(&mut node, default_value_display_length);
+            self.nodes.get_mut(&node.id).unwrap().w = node.w;
         }
+        self.draw_connections(default_value_display_length);
     }
 
-    fn draw_connection(
-        &self,
-        from_node_w: usize,
-        to_node_id: usize,
-        input_index: usize,
-        color: [u8; 3],
-        trcorner: char,
-        tlcorner: char,
-        drcorner: char,
-        dlcorner: char,
-        hedge: char,
-        vedge: char
-    ) {
-        let to_node = &self.nodes[&to_node_id];
-        let from_node = &self.nodes[&to_node.inputs[input_index].connected_id];
-
-        let mut start_pos_x = from_node.x + from_node_w - 1;
-        let mut end_pos_x = to_node.x;
-        let mut start_pos_y = from_node.y + 2;
-        let mut end_pos_y = to_node.y + input_index + 2;
-        
-        if start_pos_x > end_pos_x {
-            (start_pos_x, end_pos_x) = (end_pos_x + 1, start_pos_x + 1);
-            (start_pos_y, end_pos_y) = (end_pos_y, start_pos_y);
-        };
+    // behind the nodes, frames first so notes inside them stay readable
+    fn draw_annotations(&self) {
+        let mut annotations: Vec<(&usize, &Annotation)> = self.annotations.iter().collect();
+        annotations.sort_by_key(|(_, annotation)| matches!(annotation, Annotation::Note { .. }));
+        for (&id, annotation) in annotations {
+            let rect = annotation.get_rect(id);
+            let (w, h) = (rect.w.saturating_sub(2), rect.h.saturating_sub(2));
+            self.draw_container(annotation.get_title(id), rect.x, rect.y, w, h, &self.theme.container);
+            if let Annotation::Note { text, .. } = annotation {
+                for (i, line) in text.split('\n').enumerate() {
+                    self.put(rect.x + 1, rect.y + 1 + i, &text::pad(&text::escape_control(line), w), None);
+                }
+            }
+        }
+    }
+
+    fn draw_socket(&self, x: usize, y: usize, socket: char, data_type: MyDataType) {
+        if self.backend.borrow().get_color_mode().is_enabled() {
+            self.put(x, y, &socket.to_string(), Some(self.theme.get_type_color(data_type)));
+        } else {
+            self.put(x, y, &self.theme.get_type_glyph(data_type).to_string(), None);
+        }
+    }
 
-        let connection_len = end_pos_x - start_pos_x;
-        let connection_half_len = connection_len / 2;
+    fn draw_connections(&self, default_value_display_length: usize) {
+        // only wires near the canvas are routed, one to a node far off screen is an elbow
+        let canvas = self.canvas;
+        let (min_x, min_y) = (canvas.x.saturating_sub(ROUTE_MARGIN), canvas.y.saturating_sub(ROUTE_MARGIN));
+        let (max_x, max_y) = (canvas.x + canvas.w + ROUTE_MARGIN, canvas.y + canvas.h + ROUTE_MARGIN);
+        let mut router = Router::new(min_x, min_y, max_x - min_x + 1, max_y - min_y + 1);
+        for node in self.nodes.values() {
+            let rect = node.get_rect(default_value_display_length);
+            router.add_obstacle(rect.x, rect.y, rect.w, rect.h);
+        }
 
-        if start_pos_y == end_pos_y {
-            print!("\x1B[{};{}H{}", end_pos_y, start_pos_x + connection_half_len, hedge.to_string().truecolor(color[0], color[1], color[2]));
-        } else if start_pos_y > end_pos_y {
-            for i in (end_pos_y+1)..start_pos_y {
-                print!("\x1B[{};{}H{}", i, start_pos_x + connection_half_len, vedge.to_string().truecolor(color[0], color[1], color[2]));
+        // shorter wires first, so long ones bend around them instead of pushing them aside
+        let mut connections = Vec::new();
+        for to_node in self.nodes.values() {
+            for (i, input) in to_node.inputs.iter().enumerate() {
+                // `check` reports connections to missing nodes
+                let Some(from_node) = input.connected_id.and_then(|x| self.nodes.get(&x)) else { continue };
+                let start = from_node.get_output_start(default_value_display_length);
+                let end = to_node.get_input_end(i);
+                connections.push((start, end, self.theme.get_type_color(input.input_type), from_node.id));
             }
-            print!("\x1B[{};{}H{}", start_pos_y, start_pos_x + connection_half_len, drcorner.to_string().truecolor(color[0], color[1], color[2]));
-            print!("\x1B[{};{}H{}", end_pos_y, start_pos_x + connection_half_len, tlcorner.to_string().truecolor(color[0], color[1], color[2]));
-        } else {
-            for i in (start_pos_y+1)..end_pos_y {
-                print!("\x1B[{};{}H{}", i, start_pos_x + connection_half_len, vedge.to_string().truecolor(color[0], color[1], color[2]));
+        }
+        connections.sort_by_key(|&(start, end, _, from_node_id)| (start.0.abs_diff(end.0) + start.1.abs_diff(end.1), from_node_id, end));
+        for (start, end, color, from_node_id) in connections {
+            let path = router.route(start, end, from_node_id.0).unwrap_or_else(|| Router::elbow(start, end));
+            router.add_wire(&path, color, from_node_id.0);
+        }
+
+        for (&(x, y), cell) in &router.cells {
+            self.put(x, y, &self.theme.wire.get_glyph(cell.mask).to_string(), Some(cell.color));
+        }
+    }
+
+    fn get_theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    fn get_screen_rect(&self) -> Rect {
+        Rect::new(1, 1, self.w + 2, self.h + 2)
+    }
+
+    fn build_layout(&self) -> Container {
+        let width = self.get_screen_rect().w;
+        let mut root = Container::new(None, Direction::Vertical, Constraint::Fill);
+        let mut top = Container::new(None, Direction::Horizontal, Constraint::Fill);
+        top.add_child(Child::Container(Container::panel("Editor", Constraint::Fill, Widget::NodeCanvas)));
+        if width >= INSPECTOR_WIDTH + MIN_MAIN_PANEL_WIDTH {
+            top.add_child(Child::Container(Container::panel("Inspector", Constraint::Fixed(INSPECTOR_WIDTH), Widget::TextArea(WidgetKind::Inspector, self.get_inspector_text()))));
+        }
+        root.add_child(Child::Container(top));
+        let mut bottom = Container::new(None, Direction::Horizontal, Constraint::Percent(20));
+        bottom.add_child(Child::Container(Container::panel("Output", Constraint::Fill, Widget::Log(WidgetKind::Output, self.output.get_text(), self.output.get_scroll()))));
+        if width >= PALETTE_WIDTH + MIN_MAIN_PANEL_WIDTH {
+            let (lines, highlighted) = self.palette.get_lines();
+            bottom.add_child(Child::Container(Container::panel("Palette", Constraint::Fixed(PALETTE_WIDTH), Widget::List(WidgetKind::Palette, lines, highlighted))));
+        }
+        root.add_child(Child::Container(bottom));
+        root.add_child(Child::Widget(Constraint::Fixed(1), Widget::Prompt));
+        root.add_child(Child::Widget(Constraint::Fixed(1), Widget::TextArea(WidgetKind::StatusBar, self.get_status_text())));
+        root
+    }
+
+    fn get_output_rect(&self) -> Rect {
+        self.build_layout().find_widget(self.get_screen_rect(), WidgetKind::Output).unwrap_or_default()
+    }
+
+    fn scroll_output(&mut self, lines: isize) {
+        let rect = self.get_output_rect();
+        let max = text::wrap(&self.output.get_text(), rect.w).len().saturating_sub(rect.h);
+        self.output.scroll(lines, max);
+    }
+
+    fn draw_widget(&mut self, widget: &Widget, rect: Rect) {
+        self.clip = Some(rect);
+        match widget {
+            Widget::NodeCanvas => {
+                self.canvas = rect;
+                self.draw_annotations();
+                self.draw_nodes(5);
+            }
+            Widget::TextArea(_, text) => {
+                let lines = text::wrap(text, rect.w);
+                let end = lines.len().min(rect.h);
+                self.draw_textarea_cut_overflaw(&lines[..end].join("\n"), rect.x, rect.y, rect.w, rect.h);
             }
-            print!("\x1B[{};{}H{}", start_pos_y, start_pos_x + connection_half_len, trcorner.to_string().truecolor(color[0], color[1], color[2]));
-            print!("\x1B[{};{}H{}", end_pos_y, start_pos_x + connection_half_len, dlcorner.to_string().truecolor(color[0], color[1], color[2]));
+            Widget::Log(_, text, scroll) => {
+                let lines = text::wrap(text, rect.w);
+                let end = lines.len() - (*scroll).min(lines.len().saturating_sub(rect.h));
+                let start = end.saturating_sub(rect.h);
+                self.draw_textarea_cut_overflaw(&lines[start..end].join("\n"), rect.x, rect.y, rect.w, rect.h);
+            }
+            Widget::List(_, items, highlighted) => {
+                // scrolled just enough to keep the highlighted item in view
+                let start = highlighted.map_or(0, |x| (x + 1).saturating_sub(rect.h));
+                for i in 0..rect.h {
+                    let item = items.get(start + i).map_or("", |x| x.as_str());
+                    let color = if *highlighted == Some(start + i) { Some(self.theme.selection) } else { None };
+                    self.put(rect.x, rect.y + i, &text::pad(item, rect.w), color);
+                }
+            }
+            // drawn last by `draw_prompt` so the cursor is left on it
+            Widget::Prompt => {}
+        }
+        self.clip = None;
+    }
+
+    fn redraw_widget(&mut self, kind: WidgetKind) {
+        let layout = self.build_layout();
+        if let Some((widget, rect)) = layout.find(self.get_screen_rect(), kind) {
+            self.draw_widget(widget, rect);
         }
-        
-        print!("\x1B[{};{}H{}", start_pos_y, start_pos_x, AsgoreCore::repeat_char(hedge, connection_half_len).truecolor(color[0], color[1], color[2]));
-        print!("\x1B[{};{}H{}", end_pos_y, start_pos_x + connection_half_len + 1, AsgoreCore::repeat_char(hedge, connection_len - connection_half_len - 1).truecolor(color[0], color[1], color[2]));
     }
 
     fn draw(&mut self) {
         self.clear_win();
-        let out_h = self.h/5;
-        let editor_h = self.h - out_h -3;
-        self.draw_container("Editor".to_string(), 1, 1, self.w, editor_h, '┐', '┌', '┘', '└', '─', '─', '│', '│');
-        self.draw_nodes('╗', '╔', '╝', '╚', '─', '─', '║', '║', '╣', '╠', 5);
-        self.draw_container("Output".to_string(), 1, editor_h + 3, self.w, out_h, '┐', '┌', '┘', '└', '─', '─', '│', '│');
-    
-        print!("\x1B[{};{}H> ", self.h+5, 0);
-        io::stdout().flush().unwrap();
-    }
-
-    fn events(&mut self) -> bool {
-        let mut command = String::new();
-        io::stdin().read_line(&mut command).unwrap_or(0);
-        command = command.trim().to_owned();
-        
-        let out_h = self.h/5;
-        let editor_h = self.h - out_h -3;
-        if command.starts_with("calc_out ") {
-            let node_id: usize = command[9..].parse().unwrap_or(0); // command[9..(command.len()-2)]
-            if self.nodes.contains_key(&node_id) {
-                let text = &self.evaluate_node(node_id).get_print_text();
-                self.draw_textarea_cut_overflaw(text, 2, editor_h+4, self.w, out_h);
-            }
-        }
-        else if command.starts_with("pos ") {
-            let args: Vec<&str> = command[4..].split(" ").collect();
+        let layout = self.build_layout();
+        layout.draw(self, self.get_screen_rect());
+        if let Some(Drag::Select(start, end)) = self.drag {
+            let rect = Rect::from_corners(start, end);
+            if rect.w >= 2 && rect.h >= 2 {
+                self.clip = Some(self.canvas);
+                self.draw_rect(rect.x, rect.y, rect.w - 2, rect.h - 2, &self.theme.container, Some(self.theme.selection));
+                self.clip = None;
+            }
+        }
+        self.draw_prompt();
+    }
+
+    fn draw_prompt(&self) {
+        let rect = self.build_layout().find_widget(self.get_screen_rect(), WidgetKind::Prompt).unwrap_or(Rect::new(1, self.h + 2, self.w + 2, 1));
+        let before_cursor: String = self.command_line.chars().take(self.command_cursor).collect();
+        let mut backend = self.backend.borrow_mut();
+        backend.clear_line(rect.y);
+        backend.put(rect.x, rect.y, &("> ".to_string() + &self.command_line), None);
+        backend.set_cursor(rect.x + 2 + text::get_width(&before_cursor), rect.y);
+        backend.flush();
+    }
+
+    fn edit_command_line(&mut self, key: Key) {
+        let len = self.command_line.chars().count();
+        let byte_index = |line: &str, cursor: usize| line.char_indices().nth(cursor).map_or(line.len(), |(i, _)| i);
+        match key {
+            Key::Char(c) => {
+                let index = byte_index(&self.command_line, self.command_cursor);
+                self.command_line.insert(index, c);
+                self.command_cursor += 1;
+            }
+            Key::Backspace if self.command_cursor > 0 => {
+                self.command_cursor -= 1;
+                let index = byte_index(&self.command_line, self.command_cursor);
+                self.command_line.remove(index);
+            }
+            Key::Delete if self.command_cursor < len => {
+                let index = byte_index(&self.command_line, self.command_cursor);
+                self.command_line.remove(index);
+            }
+            Key::Left if self.command_cursor > 0 => self.command_cursor -= 1,
+            Key::Right if self.command_cursor < len => self.command_cursor += 1,
+            Key::Home => self.command_cursor = 0,
+            Key::End => self.command_cursor = len,
+            _ => {}
+        }
+    }
+
+    fn events(&mut self, terminal: &mut Terminal) -> bool {
+        match terminal.read_event() {
+            None | Some(Event::Key(Key::Interrupt)) | Some(Event::Key(Key::Eof)) => return true,
+            Some(Event::Resize) => {
+                self.update_size();
+                self.draw();
+            }
+            Some(Event::Key(Key::Enter)) => {
+                let command = std::mem::take(&mut self.command_line);
+                self.command_cursor = 0;
+                let entries = self.output.get_len();
+                if self.run_command(command.trim()) {
+                    return true;
+                }
+                // the newest log line is the outcome of the command, otherwise it just went through
+                self.status = match self.output.get_last() {
+                    Some(text) if self.output.get_len() > entries => text.lines().next().unwrap_or("").to_string(),
+                    _ => format!("done: {}", command.trim()),
+                };
+                self.palette.set_query("");
+                terminal.set_mouse(self.mouse);
+                self.update_size();
+                self.draw();
+            }
+            Some(Event::Mouse(mouse)) => self.mouse_event(mouse),
+            Some(Event::Key(key @ (Key::PageUp | Key::PageDown))) => {
+                let page = self.get_output_rect().h.saturating_sub(1).max(1) as isize;
+                self.scroll_output(if key == Key::PageUp { page } else { -page });
+                self.draw();
+            }
+            // while typing add_node the arrows move through the palette
+            Some(Event::Key(key @ (Key::Up | Key::Down))) if self.command_line.starts_with("add_node ") => {
+                self.palette.move_highlight(if key == Key::Up { -1 } else { 1 });
+                self.redraw_widget(WidgetKind::Palette);
+                self.draw_prompt();
+            }
+            Some(Event::Key(key)) => {
+                self.edit_command_line(key);
+                self.palette.set_query(self.command_line.strip_prefix("add_node ").unwrap_or(""));
+                self.redraw_widget(WidgetKind::Palette);
+                self.draw_prompt();
+            }
+        }
+        false
+    }
+
+    fn mouse_event(&mut self, mouse: Mouse) {
+        let position = (mouse.x, mouse.y);
+        match (mouse.kind, self.drag) {
+            (MouseKind::Press, _) if self.canvas.contains(mouse.x, mouse.y) => {
+                // pressing a node drags it, or the whole selection when it is part of it, elsewhere starts a rectangle
+                self.drag = match (self.get_node_at(mouse.x, mouse.y), self.get_annotation_at(mouse.x, mouse.y)) {
+                    (Some(node_id), _) => {
+                        if !self.selection.contains(&node_id) {
+                            self.set_selection(&[node_id]);
+                        }
+                        Some(Drag::Move(position))
+                    }
+                    (None, Some(id)) => Some(Drag::Annotation(id, position)),
+                    (None, None) => Some(Drag::Select(position, position)),
+                };
+            }
+            (MouseKind::Drag, Some(Drag::Move(last))) => {
+                self.move_selection(mouse.x as isize - last.0 as isize, mouse.y as isize - last.1 as isize);
+                self.dirty = true;
+                self.drag = Some(Drag::Move(position));
+            }
+            (MouseKind::Drag, Some(Drag::Annotation(id, last))) => {
+                let rect = self.annotations[&id].get_rect(id);
+                let x = rect.x.saturating_add_signed(mouse.x as isize - last.0 as isize);
+                let y = rect.y.saturating_add_signed(mouse.y as isize - last.1 as isize);
+                self.move_annotation(id, x, y);
+                self.dirty = true;
+                self.drag = Some(Drag::Annotation(id, position));
+            }
+            (MouseKind::Drag, Some(Drag::Select(start, _))) => self.drag = Some(Drag::Select(start, position)),
+            (MouseKind::Release, Some(Drag::Select(start, _))) => {
+                let node_ids = self.get_nodes_in(Rect::from_corners(start, position));
+                self.set_selection(&node_ids);
+                self.drag = None;
+            }
+            (MouseKind::Release, Some(Drag::Annotation(..))) => self.drag = None,
+            (MouseKind::Release, Some(Drag::Move(_))) => {
+                let node_ids: Vec<NodeId> = self.selection.iter().copied().collect();
+                self.settle_nodes(&node_ids);
+                self.drag = None;
+                self.auto_save();
+            }
+            (MouseKind::ScrollUp | MouseKind::ScrollDown, _) if self.get_output_rect().contains(mouse.x, mouse.y) => {
+                self.scroll_output(if mouse.kind == MouseKind::ScrollUp { 3 } else { -3 });
+            }
+            _ => return,
+        }
+        self.draw();
+    }
+
+    /// Runs a command line and evaluates the watched nodes again when it changed the graph, returns whether to quit.
+    fn run_command(&mut self, command: &str) -> bool {
+        self.changed = false;
+        let quit = self.execute_command(command);
+        if self.changed {
+            self.dirty = true;
+            self.print_watched();
+        }
+        quit
+    }
+
+    fn execute_command(&mut self, command: &str) -> bool {
+        if let Some(arg) = command.strip_prefix("calc_out ") {
+            match self.get_node_id(arg) {
+                Some(node_id) => self.print_evaluation(node_id),
+                None => self.output.message(&format!("Unknown node id {}.", arg)),
+            }
+        }
+        else if command == "calc_all" {
+            if let Some(cycle) = self.get_cycles().first() {
+                self.output.message(&format!("The graph can not be evaluated, it has the cycle {}.", format_path(cycle)));
+                return false;
+            }
+            let sinks: Vec<NodeId> = self.get_evaluation_order().into_iter()
+                .filter(|x| !self.nodes[x].output.connected_ids.iter().any(|x| self.nodes.contains_key(x)))
+                .collect();
+            if sinks.is_empty() {
+                self.output.message("No nodes to evaluate.");
+            }
+            for node_id in sinks {
+                self.print_evaluation(node_id);
+            }
+        }
+        else if command == "watch" {
+            match self.watched.is_empty() {
+                true => self.output.message("No nodes watched, use watch {node id}."),
+                false => self.output.message(&format!("Watching {}.", self.watched.iter().map(|x| x.to_string()).collect::<Vec<String>>().join(", "))),
+            }
+        }
+        else if let Some(arg) = command.strip_prefix("watch ") {
+            match self.get_node_id(arg) {
+                Some(node_id) => {
+                    self.watched.insert(node_id);
+                    self.print_evaluation(node_id);
+                }
+                None => self.output.message(&format!("Unknown node id {}.", arg)),
+            }
+        }
+        else if let Some(arg) = command.strip_prefix("unwatch ") {
+            match arg.parse().ok().filter(|x| self.watched.remove(x)) {
+                Some(node_id) => self.output.message(&format!("Node {} is no longer watched.", node_id)),
+                None => self.output.message(&format!("Node {} is not watched.", arg)),
+            }
+        }
+        else if let Some(args) = command.strip_prefix("pos ") {
+            let args: Vec<&str> = args.split(' ').collect();
             if args.len() != 3 {
+                self.output.message("Use pos {node id} {x} {y}.");
                 return false;
             }
-            let node_id = args[0].parse().unwrap_or(0);
             let x = args[1].parse().unwrap_or(0);
             let y = args[2].parse().unwrap_or(0);
-            
-            let mut node = self.nodes.get_mut(&node_id).unwrap();
-            node.x = x;
-            node.y = y;
+            let Some(node_id) = self.get_node_id(args[0]) else {
+                self.output.message(&format!("Unknown node id {}.", args[0]));
+                return false;
+            };
+            let node = self.nodes.get_mut(&node_id).unwrap();
+            node.x = x.max(1);
+            node.y = y.max(1);
+            self.settle_nodes(&[node_id]);
+            self.changed = true;
         }
-        else if command.starts_with("con ") {
-            let args: Vec<&str> = command[4..].split(" ").collect();
+        else if let Some(args) = command.strip_prefix("con ") {
+            let args: Vec<&str> = args.split(' ').collect();
             if args.len() != 3 {
+                self.output.message("Use con {from node id} {to node id} {input index}.");
+                return false;
+            }
+            let ids = (self.get_node_id(args[0]), self.get_node_id(args[1]), args[2].parse::<usize>());
+            let (Some(from_node_id), Some(to_node_id), Ok(input_index)) = ids else {
+                self.output.message("Unknown node id or input index.");
+                return false;
+            };
+            if input_index >= self.nodes[&to_node_id].inputs.len() {
+                self.output.message("Unknown node id or input index.");
                 return false;
             }
-            let from_node_id = args[0].parse().unwrap_or(0);
-            let to_node_id = args[1].parse().unwrap_or(0);
-            let input_index = args[2].parse().unwrap_or(0);
-            
-            let mut node = self.nodes.get_mut(&to_node_id).unwrap();
 
-            if node.inputs[input_index].connected_id == 0 {
+            let node = &self.nodes[&to_node_id];
+            // connecting the same pair again removes the connection, which never makes a cycle
+            if node.inputs[input_index].connected_id != Some(from_node_id) {
+                if let Some(cycle) = self.get_cycle_path(from_node_id, to_node_id, input_index) {
+                    self.output.message(&format!("Connecting {} to {} would make a cycle: {}.", from_node_id, to_node_id, format_path(&cycle)));
+                    return false;
+                }
+                // refused before the old connection is removed, so a wrong type loses nothing
+                if !self.are_types_matched(from_node_id, to_node_id, input_index) {
+                    let from_type = self.nodes[&from_node_id].output.output_type.get_name();
+                    let to_type = node.inputs[input_index].input_type.get_name();
+                    self.output.message(&format!("Node {} gives {} but input {} of node {} takes {}.", from_node_id, from_type, input_index, to_node_id, to_type));
+                    return false;
+                }
+            }
+            if node.inputs[input_index].connected_id.is_none() {
                 self.connect_nodes(from_node_id, to_node_id, input_index);
-            } else if node.inputs[input_index].connected_id == from_node_id {
+            } else if node.inputs[input_index].connected_id == Some(from_node_id) {
                 self.remove_connection(to_node_id, input_index);
             } else {
                 self.remove_connection(to_node_id, input_index);
                 self.connect_nodes(from_node_id, to_node_id, input_index);
             }
+            self.changed = true;
         }
-        else if command.starts_with("set_val ") {
-            let args: Vec<&str> = command[8..].split(" ").collect();
+        else if let Some(args) = command.strip_prefix("set_val ") {
+            let args: Vec<&str> = args.split(' ').collect();
             if args.len() != 3 {
+                self.output.message("Use set_val {node id} {input index} {value}.");
                 return false;
             }
-            let node_id = args[0].parse().unwrap_or(0);
-            let input_index = args[1].parse().unwrap_or(0);
-            
-            let mut node = self.nodes.get_mut(&node_id).unwrap();
+            let (node_id, input_index) = (self.get_node_id(args[0]), args[1].parse::<usize>());
+            let (Some(node_id), Ok(input_index)) = (node_id, input_index) else {
+                self.output.message("Unknown node id or input index.");
+                return false;
+            };
+            let Some(node) = self.nodes.get_mut(&node_id).filter(|x| input_index < x.inputs.len()) else {
+                self.output.message("Unknown node id or input index.");
+                return false;
+            };
             node.inputs[input_index].value = node.inputs[input_index].value.parse_value(args[2]);
+            self.changed = true;
         }
-        else if command.starts_with("add_node ") {
-            let node_title = &command[9..];
-            if let Some(node) = MyNodeTemplate::get_node_by_title(node_title) {
-                let mut node = node.new();
-                node.x = 2;
-                node.y = 2;
-                self.add_node(node);
+        else if let Some(arg) = command.strip_prefix("inspect ") {
+            match arg.parse() {
+                Ok(node_id) if self.nodes.contains_key(&node_id) => self.set_selection(&[node_id]),
+                _ => self.output.message(&format!("Unknown node id {}.", arg)),
+            }
+        }
+        else if let Some(args) = command.strip_prefix("edit ") {
+            // unlike set_val the value may contain spaces
+            let (index, value) = args.split_once(' ').unwrap_or((args, ""));
+            let node = self.selected.and_then(|x| self.nodes.get_mut(&x));
+            match (node, index.parse::<usize>()) {
+                (Some(node), Ok(index)) if index < node.inputs.len() => {
+                    node.inputs[index].value = node.inputs[index].value.parse_value(value);
+                    self.changed = true;
+                    self.auto_save();
+                }
+                (None, _) => self.output.message("No node selected, use inspect {node id}."),
+                _ => self.output.message(&format!("Unknown input index {}.", index)),
+            }
+        }
+        else if let Some(args) = command.strip_prefix("rename ") {
+            // the name may contain spaces
+            let (node_id, name) = args.split_once(' ').unwrap_or((args, ""));
+            let result = match node_id.parse() {
+                Ok(node_id) => self.rename_node(node_id, name),
+                Err(_) => Err("Use rename {node id} {name}.".to_string()),
+            };
+            match result {
+                Ok(()) => self.changed = true,
+                Err(message) => self.output.message(&message),
+            }
+        }
+        else if let Some(args) = command.strip_prefix("rename_input ") {
+            let parts: Vec<&str> = args.splitn(3, ' ').collect();
+            let result = match (parts.first().and_then(|x| x.parse().ok()), parts.get(1).and_then(|x| x.parse().ok())) {
+                (Some(node_id), Some(input_index)) => self.rename_input(node_id, input_index, parts.get(2).unwrap_or(&"")),
+                _ => Err("Use rename_input {node id} {input index} {label}.".to_string()),
             };
+            match result {
+                Ok(()) => self.changed = true,
+                Err(message) => self.output.message(&message),
+            }
+        }
+        else if let Some(args) = command.strip_prefix("select ") {
+            if args == "all" {
+                let node_ids: Vec<NodeId> = self.nodes.keys().copied().collect();
+                self.set_selection(&node_ids);
+            } else if args == "none" {
+                self.set_selection(&[]);
+            } else if let Some(title) = args.strip_prefix("type ") {
+                let title = title.trim_matches('"');
+                let node_ids: Vec<NodeId> = self.nodes.values().filter(|x| x.title == title).map(|x| x.id).collect();
+                self.set_selection(&node_ids);
+            } else if let Some(args) = args.strip_prefix("rect ") {
+                match args.split(' ').map(|x| x.parse().ok()).collect::<Option<Vec<usize>>>().as_deref() {
+                    Some(&[x, y, w, h]) => {
+                        let node_ids = self.get_nodes_in(Rect::new(x, y, w, h));
+                        self.set_selection(&node_ids);
+                    }
+                    _ => self.output.message("Use select rect {x} {y} {w} {h}."),
+                }
+            } else {
+                match args.split(' ').map(|x| x.parse().ok()).collect::<Option<Vec<NodeId>>>() {
+                    Some(node_ids) => self.set_selection(&node_ids),
+                    None => self.output.message("Use select {node ids}, all, none, type {node title} or rect {x} {y} {w} {h}."),
+                }
+            }
+            if self.selection.is_empty() {
+                self.output.message("No nodes selected.");
+            }
         }
-        else if command.starts_with("del_node ") {
-            if let Ok(node_id) = command[9..].parse() {
-                self.remove_node(node_id);
+        else if let Some(args) = command.strip_prefix("move ") {
+            match args.split(' ').map(|x| x.parse().ok()).collect::<Option<Vec<isize>>>().as_deref() {
+                Some(&[dx, dy]) => {
+                    self.move_selection(dx, dy);
+                    let node_ids: Vec<NodeId> = self.selection.iter().copied().collect();
+                    self.settle_nodes(&node_ids);
+                    self.changed = true;
+                    self.auto_save();
+                }
+                _ => self.output.message("Use move {x offset} {y offset}."),
             }
         }
-        else if command.starts_with("save ") {
-            let filepath = &(command[5..].to_string() + ".ane");
-            self.save(filepath);
+        else if command == "delete" {
+            self.delete_selection();
+            self.changed = true;
+        }
+        else if command == "duplicate" {
+            self.duplicate_selection();
+            self.changed = true;
+        }
+        else if command == "copy" {
+            match self.copy_selection() {
+                Ok(count) => self.output.message(&format!("Copied {} nodes.", count)),
+                Err(message) => self.output.message(&message),
+            }
         }
-        else if command.starts_with("load ") {
-            let filepath = &(command[5..].to_string() + ".ane");
-            *self = self.load(filepath);
+        else if command == "cut" {
+            match self.cut_selection() {
+                Ok(count) => {
+                    self.output.message(&format!("Cut {} nodes.", count));
+                    self.changed = true;
+                }
+                Err(message) => self.output.message(&message),
+            }
+        }
+        else if command == "paste" || command == "paste keep" {
+            match self.paste(command == "paste keep") {
+                Ok(count) => {
+                    self.output.message(&format!("Pasted {} nodes.", count));
+                    self.changed = true;
+                }
+                Err(message) => self.output.message(&message),
+            }
+        }
+        else if let Some(side) = command.strip_prefix("align ") {
+            match self.align_selection(side) {
+                Ok(()) => self.changed = true,
+                Err(message) => self.output.message(&message),
+            }
+        }
+        else if let Some(args) = command.strip_prefix("set ") {
+            let (input, value) = args.split_once(' ').unwrap_or((args, ""));
+            let count = self.set_selection_value(input, value);
+            self.output.message(&format!("Set {} on {} nodes.", input, count));
+            if count > 0 {
+                self.changed = true;
+            }
+        }
+        else if command == "arrange" || command == "arrange selection" {
+            self.arrange(command == "arrange selection");
+            self.changed = true;
+        }
+        else if let Some(direction) = command.strip_prefix("distribute ") {
+            match self.distribute_selection(direction) {
+                Ok(()) => self.changed = true,
+                Err(message) => self.output.message(&message),
+            }
+        }
+        else if let Some(arg) = command.strip_prefix("grid ") {
+            match arg.parse() {
+                Ok(size) => self.grid = size,
+                Err(_) if arg == "off" => self.grid = 0,
+                Err(_) => self.output.message("Use grid {cell size} or grid off."),
+            }
+        }
+        else if command == "mouse on" {
+            self.mouse = true;
+        }
+        else if command == "mouse off" {
+            self.mouse = false;
+        }
+        else if let Some(query) = command.strip_prefix("add_node ") {
+            match self.create_palette_node(query) {
+                Some(node) => {
+                    let node_id = self.add_node(node);
+                    self.settle_nodes(&[node_id]);
+                    self.set_selection(&[node_id]);
+                    self.changed = true;
+                }
+                None => self.output.message(&format!("Unknown node, available nodes: {}.", MyNodeTemplate::get_node_titles().join(", "))),
+            }
+        }
+        else if let Some(args) = command.strip_prefix("add_node_as ") {
+            let (node_id, query) = args.split_once(' ').unwrap_or((args, ""));
+            match (node_id.parse(), self.create_palette_node(query)) {
+                (Ok(node_id), Some(node)) => match self.add_node_with_id(node, node_id) {
+                    Ok(node_id) => {
+                        self.settle_nodes(&[node_id]);
+                        self.set_selection(&[node_id]);
+                        self.changed = true;
+                    }
+                    Err(message) => self.output.message(&message),
+                },
+                _ => self.output.message("Use add_node_as {node id} {node title}."),
+            }
+        }
+        else if command == "step" || command.starts_with("step ") {
+            let count = match command.strip_prefix("step ").map(|x| x.parse::<usize>()) {
+                None => 1,
+                Some(Ok(count)) => count,
+                Some(Err(_)) => {
+                    self.output.message("Use step {number of ticks}.");
+                    return false;
+                }
+            };
+            if let Some(cycle) = self.get_cycles().first() {
+                self.output.message(&format!("The graph can not be stepped, it has the cycle {}.", format_path(cycle)));
+                return false;
+            }
+            for _ in 0..count {
+                self.step();
+            }
+            self.output.message(&format!("Stepped {} ticks.", count));
+            self.changed = true;
+        }
+        else if command == "cycles" {
+            let cycles = self.get_cycles();
+            if cycles.is_empty() {
+                self.output.message("No cycles found.");
+            }
+            for cycle in cycles {
+                self.output.message(&format!("Cycle: {}.", format_path(&cycle)));
+            }
+        }
+        else if command == "check" {
+            let problems = self.check();
+            if problems.is_empty() {
+                self.output.message("No problems found.");
+            }
+            for problem in problems {
+                self.output.message(&format!("Problem: {}", problem));
+            }
+        }
+        else if command == "repair" {
+            let problems = self.repair();
+            if problems.is_empty() {
+                self.output.message("Nothing to repair.");
+            } else {
+                self.changed = true;
+            }
+            for problem in problems {
+                self.output.message(&format!("Repaired: {}", problem));
+            }
+        }
+        else if command == "renumber" {
+            self.renumber();
+            self.changed = true;
+        }
+        else if let Some(args) = command.strip_prefix("add_frame ") {
+            // around the selected nodes, or at the given position and size
+            let parts: Vec<&str> = args.splitn(5, ' ').collect();
+            let rect = match (parts.as_slice(), self.get_selection_rect()) {
+                (["selection", ..], Some(rect)) => Some((Rect::new(rect.x.saturating_sub(2).max(1), rect.y.saturating_sub(2).max(1), rect.w + 4, rect.h + 3), args.split_once(' ').map_or("", |x| x.1))),
+                ([x, y, w, h, title], _) => match (x.parse(), y.parse(), w.parse(), h.parse()) {
+                    (Ok(x), Ok(y), Ok(w), Ok(h)) => Some((Rect::new(x, y, w, h), *title)),
+                    _ => None,
+                },
+                _ => None,
+            };
+            match rect {
+                Some((rect, title)) => {
+                    self.add_annotation(Annotation::Frame { title: title.to_string(), rect });
+                    self.changed = true;
+                }
+                None => self.output.message("Use add_frame {x} {y} {w} {h} {title} or add_frame selection {title}."),
+            }
+        }
+        else if let Some(args) = command.strip_prefix("add_note ") {
+            let parts: Vec<&str> = args.splitn(3, ' ').collect();
+            match (parts.first().and_then(|x| x.parse().ok()), parts.get(1).and_then(|x| x.parse().ok()), parts.get(2)) {
+                (Some(x), Some(y), Some(text)) => {
+                    // \n in the text starts a new line
+                    self.add_annotation(Annotation::Note { text: AsgoreCore::fix_escape_chars(text), x, y });
+                    self.changed = true;
+                }
+                _ => self.output.message("Use add_note {x} {y} {text}."),
+            }
+        }
+        else if let Some(args) = command.strip_prefix("pos_annotation ") {
+            match args.split(' ').map(|x| x.parse().ok()).collect::<Option<Vec<usize>>>().as_deref() {
+                Some(&[id, x, y]) if self.annotations.contains_key(&id) => {
+                    self.move_annotation(id, x, y);
+                    self.changed = true;
+                }
+                _ => self.output.message("Use pos_annotation {annotation id} {x} {y}."),
+            }
+        }
+        else if let Some(arg) = command.strip_prefix("del_annotation ") {
+            match arg.parse().ok().and_then(|x| self.annotations.remove(&x)) {
+                Some(_) => {
+                    self.changed = true;
+                    self.auto_save();
+                }
+                None => self.output.message(&format!("Unknown annotation id {}.", arg)),
+            }
+        }
+        else if let Some(arg) = command.strip_prefix("del_node ") {
+            match arg.parse() {
+                Ok(node_id) if self.nodes.contains_key(&node_id) => {
+                    self.remove_node(node_id);
+                    self.changed = true;
+                }
+                _ => self.output.message(&format!("Unknown node id {}.", arg)),
+            }
+        }
+        else if let Some(arg) = command.strip_prefix("save ") {
+            let filepath = &(arg.to_string() + ".ane");
+            match self.save(filepath) {
+                Ok(()) => {
+                    self.file = Some(filepath.to_string());
+                    self.dirty = false;
+                }
+                Err(message) => self.output.message(&message),
+            }
+        }
+        else if let Some(arg) = command.strip_prefix("load ") {
+            let filepath = &(arg.to_string() + ".ane");
+            match Self::load(filepath) {
+                Ok(editor) => {
+                    // the loaded file only holds the graph, the session state is kept except the
+                    // selection, which named nodes of the old graph
+                    let old = std::mem::replace(self, editor);
+                    self.theme = old.theme;
+                    self.backend = old.backend;
+                    self.output = old.output;
+                    self.grid = old.grid;
+                    self.mouse = old.mouse;
+                    self.canvas = old.canvas;
+                    (self.w, self.h) = (old.w, old.h);
+                    self.output.message(&format!("Loaded {}.", filepath));
+                    let (watched, missing): (BTreeSet<NodeId>, BTreeSet<NodeId>) = old.watched.into_iter().partition(|x| self.nodes.contains_key(x));
+                    self.watched = watched;
+                    if !missing.is_empty() {
+                        let missing: Vec<String> = missing.iter().map(|x| x.to_string()).collect();
+                        self.output.message(&format!("No longer watching {}, the file has no such nodes.", missing.join(", ")));
+                    }
+                    self.file = Some(filepath.to_string());
+                    for problem in self.check() {
+                        self.output.message(&format!("Problem: {} Use repair to fix it.", problem));
+                    }
+                    for cycle in self.get_cycles() {
+                        self.output.message(&format!("Cycle: {}, use con to break it.", format_path(&cycle)));
+                    }
+                    // the watched nodes show their values in the loaded graph, which is not modified yet
+                    self.print_watched();
+                }
+                Err(message) => self.output.message(&message),
+            }
+        }
+        else if let Some(name) = command.strip_prefix("theme ") {
+            self.set_theme(name);
+        }
+        else if let Some(name) = command.strip_prefix("color ") {
+            match ColorMode::get_by_name(name) {
+                Some(color_mode) => self.backend.borrow_mut().set_color_mode(color_mode),
+                None => self.output.message("Unknown color mode, use auto, truecolor, 256, 16 or off."),
+            }
         }
         else if command == "autosave on" {
-            self.auto_save = true;
+            self.enable_auto_save();
         }
         else if command == "autosave off" {
-            self.auto_save = false;
+            self.disable_auto_save();
+        }
+        else if command == "out clear" {
+            self.output.clear();
+        }
+        else if let Some(arg) = command.strip_prefix("out scroll ") {
+            match arg.parse() {
+                Ok(lines) => self.scroll_output(lines),
+                Err(_) => self.output.message("Use out scroll {lines}, negative numbers scroll down."),
+            }
+        }
+        else if let Some(arg) = command.strip_prefix("out save ") {
+            match self.output.save(arg) {
+                Ok(()) => self.output.message(&format!("Output saved to {}.", arg)),
+                Err(message) => self.output.message(&message),
+            }
         }
         else if command == "q" {
             return true;
         }
         else {
-            println!("Unknown command, please try again.");
+            self.output.message(&format!("Unknown command {}, please try again.", command));
         }
 
         false
     }
     
     fn run(&mut self) {
-        loop {
-            let size = terminal_size();
-            if let Some((Width(w), Height(h))) = size {
-                self.w = w as usize - 2;
-                self.h = h as usize - 2;
+        if let Some(path) = theme::get_config_path().filter(|x| x.exists()) {
+            match Theme::load(&path.to_string_lossy()) {
+                Ok(theme) => self.theme = theme,
+                Err(message) => self.output.message(&message),
             }
-            self.draw();
+        }
+        let mut terminal = Terminal::new();
+        self.update_size();
+        self.draw();
+        while !self.events(&mut terminal) {}
+    }
 
-            if self.events() {
-                break;
-            }
+    fn update_size(&mut self) {
+        let size = self.backend.borrow().get_size();
+        if let Some((w, h)) = size {
+            self.w = w.saturating_sub(2);
+            self.h = h.saturating_sub(2);
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,222 @@
+use serde::{Deserialize, Serialize};
+use crate::editor::UI;
+
+#[derive(Clone, Copy, Default)]
+#[derive(PartialEq)]
+#[derive(Debug)]
+#[derive(Serialize, Deserialize)]
+pub struct Rect {
+    pub x: usize,
+    pub y: usize,
+    pub w: usize,
+    pub h: usize,
+}
+impl Rect {
+    pub fn new(x: usize, y: usize, w: usize, h: usize) -> Self {
+        Self { x, y, w, h }
+    }
+
+    // the area left inside a one cell border
+    pub fn inner(&self) -> Rect {
+        Rect::new(self.x + 1, self.y + 1, self.w.saturating_sub(2), self.h.saturating_sub(2))
+    }
+
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        x >= self.x && y >= self.y && x < self.x + self.w && y < self.y + self.h
+    }
+
+    pub fn contains_rect(&self, other: &Rect) -> bool {
+        other.x >= self.x && other.y >= self.y && other.x + other.w <= self.x + self.w && other.y + other.h <= self.y + self.h
+    }
+
+    pub fn intersects(&self, other: &Rect) -> bool {
+        self.x < other.x + other.w && other.x < self.x + self.w && self.y < other.y + other.h && other.y < self.y + self.h
+    }
+
+    // the smallest rectangle holding both corners
+    pub fn from_corners(a: (usize, usize), b: (usize, usize)) -> Rect {
+        Rect::new(a.0.min(b.0), a.1.min(b.1), a.0.abs_diff(b.0) + 1, a.1.abs_diff(b.1) + 1)
+    }
+
+    pub fn center(&self) -> (usize, usize) {
+        (self.x + self.w / 2, self.y + self.h / 2)
+    }
+}
+
+/// How much of its parent's space a child takes along the parent's direction.
+#[derive(Clone, Copy)]
+pub enum Constraint {
+    Fixed(usize),
+    Percent(usize),
+    Fill,
+}
+
+#[derive(Clone, Copy)]
+#[derive(PartialEq)]
+pub enum Direction {
+    Vertical,
+    Horizontal,
+}
+
+#[derive(Clone, Copy)]
+#[derive(PartialEq)]
+pub enum WidgetKind {
+    NodeCanvas,
+    Output,
+    Inspector,
+    Palette,
+    Prompt,
+    StatusBar,
+}
+
+pub enum Widget {
+    NodeCanvas,
+    // the text is wrapped to the width and shown from the top
+    TextArea(WidgetKind, String),
+    // like a text area but kept at the end, the number is how many lines it is scrolled up from there
+    Log(WidgetKind, String, usize),
+    // items and the highlighted index
+    List(WidgetKind, Vec<String>, Option<usize>),
+    Prompt,
+}
+impl Widget {
+    pub fn get_kind(&self) -> WidgetKind {
+        match self {
+            Widget::NodeCanvas => WidgetKind::NodeCanvas,
+            Widget::TextArea(kind, _) => *kind,
+            Widget::Log(kind, _, _) => *kind,
+            Widget::List(kind, _, _) => *kind,
+            Widget::Prompt => WidgetKind::Prompt,
+        }
+    }
+}
+
+pub enum Child {
+    Container(Container),
+    Widget(Constraint, Widget),
+}
+impl Child {
+    fn get_constraint(&self) -> Constraint {
+        match self {
+            Child::Container(container) => container.size,
+            Child::Widget(size, _) => *size,
+        }
+    }
+}
+
+/// A node of the widget tree, its children are placed one after another along `direction`.
+pub struct Container {
+    // a bordered panel is drawn around the children when there is a title
+    title: Option<String>,
+    direction: Direction,
+    size: Constraint,
+    children: Vec<Child>,
+}
+impl Container {
+    pub fn new(title: Option<&str>, direction: Direction, size: Constraint) -> Self {
+        Self {
+            title: title.map(|x| x.to_string()),
+            direction,
+            size,
+            children: Vec::new(),
+        }
+    }
+
+    pub fn panel(title: &str, size: Constraint, widget: Widget) -> Self {
+        let mut container = Self::new(Some(title), Direction::Vertical, size);
+        container.add_child(Child::Widget(Constraint::Fill, widget));
+        container
+    }
+
+    pub fn add_child(&mut self, child: Child) {
+        self.children.push(child);
+    }
+
+    fn get_content_rect(&self, rect: Rect) -> Rect {
+        if self.title.is_some() { rect.inner() } else { rect }
+    }
+
+    /// Splits `rect` between the children, fixed sizes first, then percentages, and fill children share the rest.
+    pub fn split(&self, rect: Rect) -> Vec<Rect> {
+        let content = self.get_content_rect(rect);
+        let total = if self.direction == Direction::Vertical { content.h } else { content.w };
+        let mut sizes = vec![0; self.children.len()];
+        let mut left = total;
+        for (i, child) in self.children.iter().enumerate() {
+            if let Constraint::Fixed(size) = child.get_constraint() {
+                sizes[i] = size.min(left);
+                left -= sizes[i];
+            }
+        }
+        for (i, child) in self.children.iter().enumerate() {
+            if let Constraint::Percent(percent) = child.get_constraint() {
+                sizes[i] = (total * percent / 100).min(left);
+                left -= sizes[i];
+            }
+        }
+        let fill_count = self.children.iter().filter(|x| matches!(x.get_constraint(), Constraint::Fill)).count();
+        let mut fill_index = 0;
+        for (i, child) in self.children.iter().enumerate() {
+            if let Constraint::Fill = child.get_constraint() {
+                fill_index += 1;
+                // the last fill child takes the remainder of the division
+                sizes[i] = if fill_index == fill_count { left } else { left / (fill_count - fill_index + 1) };
+                left -= sizes[i];
+            }
+        }
+
+        let mut offset = 0;
+        sizes.iter().map(|&size| {
+            let child_rect = match self.direction {
+                Direction::Vertical => Rect::new(content.x, content.y + offset, content.w, size),
+                Direction::Horizontal => Rect::new(content.x + offset, content.y, size, content.h),
+            };
+            offset += size;
+            child_rect
+        }).collect()
+    }
+
+    pub fn find(&self, rect: Rect, kind: WidgetKind) -> Option<(&Widget, Rect)> {
+        for (child, child_rect) in self.children.iter().zip(self.split(rect)) {
+            let found = match child {
+                Child::Container(container) => container.find(child_rect, kind),
+                Child::Widget(_, widget) if widget.get_kind() == kind => Some((widget, child_rect)),
+                Child::Widget(_, _) => None,
+            };
+            if found.is_some() {
+                return found;
+            }
+        }
+        None
+    }
+
+    pub fn find_widget(&self, rect: Rect, kind: WidgetKind) -> Option<Rect> {
+        self.find(rect, kind).map(|(_, rect)| rect)
+    }
+}
+
+pub trait Drawable {
+    fn draw<U: UI>(&self, ui: &mut U, rect: Rect);
+}
+impl Drawable for Container {
+    fn draw<U: UI>(&self, ui: &mut U, rect: Rect) {
+        if let Some(title) = &self.title {
+            if rect.w < 2 || rect.h < 2 {
+                return;
+            }
+            let border = ui.get_theme().container;
+            ui.draw_container(title.to_string(), rect.x, rect.y, rect.w - 2, rect.h - 2, &border);
+        }
+        for (child, child_rect) in self.children.iter().zip(self.split(rect)) {
+            child.draw(ui, child_rect);
+        }
+    }
+}
+impl Drawable for Child {
+    fn draw<U: UI>(&self, ui: &mut U, rect: Rect) {
+        match self {
+            Child::Container(container) => container.draw(ui, rect),
+            Child::Widget(_, widget) => ui.draw_widget(widget, rect),
+        }
+    }
+}
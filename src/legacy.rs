@@ -0,0 +1,44 @@
+use std::collections::HashMap;
+use serde::Deserialize;
+use crate::editor::{MyDataType, MyNodeTemplate, MyValueType};
+
+// The layout of files saved before the format version header, kept only to load them.
+// Connection ids were plain numbers with 0 for none, and sockets stored their colour.
+
+#[derive(Deserialize)]
+pub struct Input {
+    pub connected_id: usize,
+    pub input_type: MyDataType,
+    pub _color: [u8; 3],
+    pub label: String,
+    pub value: MyValueType,
+}
+
+#[derive(Deserialize)]
+pub struct Output {
+    pub connected_ids: Vec<usize>,
+    pub output_type: MyDataType,
+    pub _color: [u8; 3],
+}
+
+#[derive(Deserialize)]
+pub struct Node {
+    pub id: usize,
+    pub title: String,
+    pub node_type: MyNodeTemplate,
+    pub inputs: Vec<Input>,
+    pub output: Output,
+    pub x: usize,
+    pub y: usize,
+    pub w: usize,
+}
+
+#[derive(Deserialize)]
+pub struct Editor {
+    pub nodes: HashMap<usize, Node>,
+    pub _outputs_cache: HashMap<usize, MyValueType>,
+    pub current_id: usize,
+    pub auto_save: bool,
+    pub _w: usize,
+    pub _h: usize,
+}
@@ -1,18 +1,35 @@
+mod annotation;
+mod arrange;
+mod backend;
+mod clipboard;
 mod editor;
+mod color;
+mod layout;
+mod legacy;
+#[cfg(test)]
+mod memory_backend;
+mod output;
+mod palette;
+mod router;
+mod text;
+mod terminal;
+#[cfg(test)]
+mod tests;
+mod theme;
 use editor::*;
 
 // const TITLE: &str = "Asgore Terminal Node Editor";
 
 fn main() {
-    let mut node1 = MyNodeTemplate::RepeatString.new();
+    let mut node1 = MyNodeTemplate::RepeatString.create_node();
     node1.inputs[0].value = MyValueType::String { value: "fdgasd".to_string() };
     node1.x = 70;
     node1.y = 15;
-    let mut node2 = MyNodeTemplate::NewNumber.new();
+    let mut node2 = MyNodeTemplate::NewNumber.create_node();
     node2.inputs[0].value = MyValueType::Isize { value: 4 };
     node2.x = 30;
     node2.y = 16;
-    let mut node3 = MyNodeTemplate::NewNumber.new();
+    let mut node3 = MyNodeTemplate::NewNumber.create_node();
     node3.inputs[0].value = MyValueType::Isize { value: 4 };
     node3.x = 25;
     node3.y = 6;
@@ -21,7 +38,7 @@ fn main() {
     editor.add_node(node1);
     editor.add_node(node2);
     editor.add_node(node3);
-    editor.connect_nodes(2, 1, 1);
+    editor.connect_nodes(NodeId(2), NodeId(1), 1);
 
     // editor.clear_win();
     // editor.draw_container("Editor".to_string(), 1, 1, 100, 20, '┐', '┌', '┘', '└', '─', '─', '│', '│');
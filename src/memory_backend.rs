@@ -0,0 +1,158 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+use unicode_segmentation::UnicodeSegmentation;
+use crate::backend::Backend;
+use crate::color::ColorMode;
+use crate::text;
+
+#[derive(Clone)]
+struct Cell {
+    // one grapheme, empty for the right half of a wide one
+    text: String,
+    color: Option<[u8; 3]>,
+}
+
+/// The cells of a `MemoryBackend`, shared so they can still be read after the backend is handed over.
+pub struct Screen {
+    w: usize,
+    h: usize,
+    cells: Vec<Cell>,
+    pub cursor: (usize, usize),
+}
+impl Screen {
+    fn clear_cell(&mut self, x: usize, y: usize) {
+        if x < self.w && y < self.h {
+            self.cells[y * self.w + x] = Cell { text: " ".to_string(), color: None };
+        }
+    }
+
+    fn set_cell(&mut self, x: usize, y: usize, text: &str, color: Option<[u8; 3]>) {
+        if x >= self.w || y >= self.h {
+            return;
+        }
+        // overwriting half of a wide grapheme leaves a blank in its other half
+        if self.cells[y * self.w + x].text.is_empty() && x > 0 {
+            self.clear_cell(x - 1, y);
+        }
+        if text::get_width(&self.cells[y * self.w + x].text) == 2 {
+            self.clear_cell(x + 1, y);
+        }
+        self.cells[y * self.w + x] = Cell { text: text.to_string(), color };
+    }
+
+    /// The text of every row, then the colours as letters under a legend of their hex values.
+    pub fn get_snapshot(&self) -> String {
+        let mut legend: BTreeMap<[u8; 3], char> = BTreeMap::new();
+        for cell in &self.cells {
+            if let Some(color) = cell.color {
+                let next = (b'a' + legend.len() as u8) as char;
+                legend.entry(color).or_insert(next);
+            }
+        }
+        let mut snapshot = String::new();
+        for row in self.cells.chunks(self.w) {
+            let line: String = row.iter().map(|x| x.text.as_str()).collect();
+            snapshot += line.trim_end();
+            snapshot += "\n";
+        }
+        if legend.is_empty() {
+            return snapshot;
+        }
+        snapshot += "--- colors\n";
+        let mut letters: Vec<(&char, &[u8; 3])> = legend.iter().map(|(color, letter)| (letter, color)).collect();
+        letters.sort();
+        for (letter, color) in letters {
+            snapshot += &format!("{} #{:02x}{:02x}{:02x}\n", letter, color[0], color[1], color[2]);
+        }
+        for row in self.cells.chunks(self.w) {
+            let line: String = row.iter().filter(|x| !x.text.is_empty()).map(|x| x.color.map_or(' ', |c| legend[&c])).collect();
+            snapshot += line.trim_end();
+            snapshot += "\n";
+        }
+        snapshot
+    }
+}
+
+/// Draws into memory instead of a terminal, for tests.
+pub struct MemoryBackend {
+    screen: Rc<RefCell<Screen>>,
+    color_mode: ColorMode,
+}
+impl MemoryBackend {
+    pub fn new(w: usize, h: usize) -> Self {
+        Self {
+            screen: Rc::new(RefCell::new(Screen {
+                w,
+                h,
+                cells: vec![Cell { text: " ".to_string(), color: None }; w * h],
+                cursor: (1, 1),
+            })),
+            color_mode: ColorMode::TrueColor,
+        }
+    }
+
+    pub fn get_screen(&self) -> Rc<RefCell<Screen>> {
+        Rc::clone(&self.screen)
+    }
+}
+impl Backend for MemoryBackend {
+    fn get_size(&self) -> Option<(usize, usize)> {
+        let screen = self.screen.borrow();
+        Some((screen.w, screen.h))
+    }
+
+    fn get_color_mode(&self) -> ColorMode {
+        self.color_mode
+    }
+
+    fn set_color_mode(&mut self, color_mode: ColorMode) {
+        self.color_mode = color_mode;
+    }
+
+    fn clear(&mut self) {
+        let mut screen = self.screen.borrow_mut();
+        let (w, h) = (screen.w, screen.h);
+        screen.cells = vec![Cell { text: " ".to_string(), color: None }; w * h];
+    }
+
+    fn clear_line(&mut self, y: usize) {
+        if y == 0 {
+            return;
+        }
+        let mut screen = self.screen.borrow_mut();
+        for x in 0..screen.w {
+            screen.clear_cell(x, y - 1);
+        }
+    }
+
+    fn put(&mut self, x: usize, y: usize, text: &str, color: Option<[u8; 3]>) {
+        if x == 0 || y == 0 {
+            return;
+        }
+        let color = color.filter(|_| self.color_mode.is_enabled());
+        let mut screen = self.screen.borrow_mut();
+        let mut x = x - 1;
+        for grapheme in text.graphemes(true) {
+            let width = text::get_width(grapheme);
+            if width == 0 {
+                continue;
+            }
+            screen.set_cell(x, y - 1, grapheme, color);
+            if width == 2 && x + 1 < screen.w {
+                let index = (y - 1) * screen.w + x + 1;
+                if text::get_width(&screen.cells[index].text) == 2 {
+                    screen.clear_cell(x + 2, y - 1);
+                }
+                screen.cells[index] = Cell { text: String::new(), color };
+            }
+            x += width;
+        }
+    }
+
+    fn set_cursor(&mut self, x: usize, y: usize) {
+        self.screen.borrow_mut().cursor = (x, y);
+    }
+
+    fn flush(&mut self) {}
+}
@@ -0,0 +1,101 @@
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::text;
+
+// the local time of day as hh:mm:ss
+fn get_time() -> String {
+    let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |x| x.as_secs());
+    #[cfg(unix)]
+    unsafe {
+        let time = seconds as libc::time_t;
+        let mut local: libc::tm = std::mem::zeroed();
+        if !libc::localtime_r(&time, &mut local).is_null() {
+            return format!("{:02}:{:02}:{:02}", local.tm_hour, local.tm_min, local.tm_sec);
+        }
+    }
+    format!("{:02}:{:02}:{:02}", seconds / 3600 % 24, seconds / 60 % 60, seconds % 60)
+}
+
+pub struct LogEntry {
+    time: String,
+    // the node an evaluation result came from, `None` for messages
+    node_id: Option<usize>,
+    text: String,
+}
+impl LogEntry {
+    pub fn get_print_text(&self) -> String {
+        let prefix = match self.node_id {
+            Some(node_id) => format!("[{}] #{}: ", self.time, node_id),
+            None => format!("[{}] ", self.time),
+        };
+        // continuation lines are indented under the first one
+        let indent = "\n".to_string() + &" ".repeat(prefix.chars().count());
+        prefix + &text::escape_control_lines(&self.text).replace('\n', &indent)
+    }
+
+    // the entry without its time
+    pub fn get_untimed_text(&self) -> String {
+        match self.node_id {
+            Some(node_id) => format!("#{}: {}", node_id, self.text),
+            None => self.text.clone(),
+        }
+    }
+}
+
+/// History of evaluation results and messages shown in the Output panel.
+#[derive(Default)]
+pub struct OutputLog {
+    entries: Vec<LogEntry>,
+    // how many lines the view is scrolled up from the newest line
+    scroll: usize,
+}
+impl OutputLog {
+    pub fn push(&mut self, node_id: Option<usize>, text: &str) {
+        self.entries.push(LogEntry {
+            time: get_time(),
+            node_id,
+            text: text.to_string(),
+        });
+        self.scroll = 0;
+    }
+
+    pub fn message(&mut self, text: &str) {
+        self.push(None, text);
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.scroll = 0;
+    }
+
+    pub fn get_len(&self) -> usize {
+        self.entries.len()
+    }
+
+    // the newest entry without its time
+    pub fn get_last(&self) -> Option<String> {
+        self.entries.last().map(|x| x.get_untimed_text())
+    }
+
+    #[cfg(test)]
+    pub fn get_untimed_lines(&self) -> Vec<String> {
+        self.entries.iter().map(|x| x.get_untimed_text()).collect()
+    }
+
+    pub fn get_scroll(&self) -> usize {
+        self.scroll
+    }
+
+    /// Moves the view `lines` up (negative is down), `max` is the furthest it may go up.
+    pub fn scroll(&mut self, lines: isize, max: usize) {
+        self.scroll = self.scroll.saturating_add_signed(lines).min(max);
+    }
+
+    pub fn get_text(&self) -> String {
+        self.entries.iter().map(|x| x.get_print_text()).collect::<Vec<String>>().join("\n")
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        fs::write(path, self.get_text() + "\n").map_err(|_| format!("the output can not be saved to {}.", path))
+    }
+}
@@ -0,0 +1,79 @@
+use crate::editor::MyNodeTemplate;
+
+/// Scores how well `query` matches `text` as a case insensitive subsequence, lower is better.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<usize> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    for (i, c) in query.to_lowercase().chars().filter(|x| !x.is_whitespace()).enumerate() {
+        let found = text[position..].iter().position(|&x| x == c)?;
+        // characters skipped before the first match count less than gaps inside the match
+        score += if i == 0 { found } else { found * 2 };
+        position += found + 1;
+    }
+    Some(score)
+}
+
+/// The node templates filtered by the search text typed after `add_node `, grouped by category.
+#[derive(Default)]
+pub struct Palette {
+    query: String,
+    // index into the matches, not into the drawn lines
+    highlighted: usize,
+}
+impl Palette {
+    pub fn set_query(&mut self, query: &str) {
+        if self.query != query {
+            self.query = query.to_string();
+            self.highlighted = 0;
+        }
+    }
+
+    pub fn move_highlight(&mut self, steps: isize) {
+        let count = self.get_matches(&self.query).len();
+        if count > 0 {
+            self.highlighted = (self.highlighted as isize + steps).rem_euclid(count as isize) as usize;
+        }
+    }
+
+    /// Matching (category, title) pairs, categories in alphabetical order and the best matches first in each.
+    pub fn get_matches(&self, query: &str) -> Vec<(String, String)> {
+        let mut matches: Vec<(String, usize, String)> = MyNodeTemplate::get_node_titles().into_iter().filter_map(|title| {
+            let template = MyNodeTemplate::get_node_by_title(&title)?;
+            let score = fuzzy_score(query, &title)?;
+            Some((template.get_category().to_string(), score, title))
+        }).collect();
+        matches.sort();
+        matches.into_iter().map(|(category, _, title)| (category, title)).collect()
+    }
+
+    /// The title to add for `query`, an exact title wins over the highlighted match.
+    pub fn get_choice(&self, query: &str) -> Option<String> {
+        if MyNodeTemplate::get_node_by_title(query).is_some() {
+            return Some(query.to_string());
+        }
+        let matches = self.get_matches(query);
+        let index = if query == self.query { self.highlighted } else { 0 };
+        matches.get(index).or(matches.first()).map(|(_, title)| title.clone())
+    }
+
+    /// The search line, category headers and entries, with the index of the highlighted line.
+    pub fn get_lines(&self) -> (Vec<String>, Option<usize>) {
+        let mut lines = vec![format!("search: {}", self.query)];
+        let mut highlighted = None;
+        let mut category = String::new();
+        for (i, (entry_category, title)) in self.get_matches(&self.query).into_iter().enumerate() {
+            if entry_category != category {
+                lines.push(entry_category.clone());
+                category = entry_category;
+            }
+            if i == self.highlighted {
+                highlighted = Some(lines.len());
+                lines.push("> ".to_string() + &title);
+            } else {
+                lines.push("  ".to_string() + &title);
+            }
+        }
+        (lines, highlighted)
+    }
+}
@@ -0,0 +1,225 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+pub const UP: u8 = 1;
+pub const DOWN: u8 = 2;
+pub const LEFT: u8 = 4;
+pub const RIGHT: u8 = 8;
+
+const DIRECTIONS: [u8; 4] = [UP, DOWN, LEFT, RIGHT];
+
+const STEP_COST: usize = 1;
+const BEND_COST: usize = 4;
+const CROSSING_COST: usize = 2;
+const LANE_COST: usize = 12;
+const TOUCH_COST: usize = 40;
+
+fn opposite(dir: u8) -> u8 {
+    match dir {
+        UP => DOWN,
+        DOWN => UP,
+        LEFT => RIGHT,
+        _ => LEFT,
+    }
+}
+
+fn is_horizontal(dir: u8) -> bool {
+    dir == LEFT || dir == RIGHT
+}
+
+#[derive(Clone)]
+pub struct WireCell {
+    pub mask: u8,
+    pub color: [u8; 3],
+    horizontal_source: Option<usize>,
+    vertical_source: Option<usize>,
+}
+
+/// Orthogonal wire router over the editor canvas, node boxes are obstacles.
+pub struct Router {
+    x: usize,
+    y: usize,
+    w: usize,
+    h: usize,
+    blocked: Vec<bool>,
+    pub cells: HashMap<(usize, usize), WireCell>,
+}
+impl Router {
+    pub fn new(x: usize, y: usize, w: usize, h: usize) -> Self {
+        Self {
+            x,
+            y,
+            w,
+            h,
+            blocked: vec![false; w * h],
+            cells: HashMap::new(),
+        }
+    }
+
+    fn index(&self, x: usize, y: usize) -> Option<usize> {
+        if x < self.x || y < self.y || x >= self.x + self.w || y >= self.y + self.h {
+            return None;
+        }
+        Some((y - self.y) * self.w + (x - self.x))
+    }
+
+    fn step(&self, x: usize, y: usize, dir: u8) -> Option<(usize, usize)> {
+        let (nx, ny) = match dir {
+            UP => (x, y.checked_sub(1)?),
+            DOWN => (x, y + 1),
+            LEFT => (x.checked_sub(1)?, y),
+            _ => (x + 1, y),
+        };
+        self.index(nx, ny).map(|_| (nx, ny))
+    }
+
+    pub fn add_obstacle(&mut self, x: usize, y: usize, w: usize, h: usize) {
+        for j in y..(y + h) {
+            for i in x..(x + w) {
+                if let Some(index) = self.index(i, j) {
+                    self.blocked[index] = true;
+                }
+            }
+        }
+    }
+
+    /// Extra cost of passing through an already wired cell, `dir_in` and `dir_out` are the travel directions.
+    fn pass_cost(&self, x: usize, y: usize, dir_in: u8, dir_out: u8, source: usize) -> usize {
+        let cell = match self.cells.get(&(x, y)) {
+            Some(cell) => cell,
+            None => return 0,
+        };
+        let foreign = |owner: Option<usize>| owner.is_some() && owner != Some(source);
+        if !foreign(cell.horizontal_source) && !foreign(cell.vertical_source) {
+            return 0;
+        }
+        let straight = dir_in == dir_out;
+        let crossing_straight_wire = cell.mask == UP | DOWN || cell.mask == LEFT | RIGHT;
+        if straight && crossing_straight_wire && is_horizontal(dir_in) != (cell.mask == LEFT | RIGHT) {
+            return CROSSING_COST;
+        }
+        if straight && ((is_horizontal(dir_in) && foreign(cell.horizontal_source)) || (!is_horizontal(dir_in) && foreign(cell.vertical_source))) {
+            return LANE_COST;
+        }
+        TOUCH_COST
+    }
+
+    /// Finds a path from `start` to `end`, both wires leave and enter their sockets heading right.
+    pub fn route(&self, start: (usize, usize), end: (usize, usize), source: usize) -> Option<Vec<(usize, usize)>> {
+        let start_index = self.index(start.0, start.1)?;
+        let end_index = self.index(end.0, end.1)?;
+        let state = |index: usize, dir: u8| index * 4 + dir.trailing_zeros() as usize;
+        let heuristic = |x: usize, y: usize| x.abs_diff(end.0) + y.abs_diff(end.1);
+
+        let mut costs: HashMap<usize, usize> = HashMap::new();
+        let mut came_from: HashMap<usize, usize> = HashMap::new();
+        let mut open = BinaryHeap::new();
+        costs.insert(state(start_index, RIGHT), 0);
+        open.push(Reverse((heuristic(start.0, start.1), 0, start.0, start.1, RIGHT)));
+
+        while let Some(Reverse((_, cost, x, y, dir))) = open.pop() {
+            let current = state(self.index(x, y)?, dir);
+            if cost > costs[&current] {
+                continue;
+            }
+            if (x, y) == end {
+                let mut path = vec![(x, y)];
+                let mut current = current;
+                while let Some(&previous) = came_from.get(&current) {
+                    let index = previous / 4;
+                    path.push((self.x + index % self.w, self.y + index / self.w));
+                    current = previous;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for new_dir in DIRECTIONS {
+                if new_dir == opposite(dir) {
+                    continue;
+                }
+                let (nx, ny) = match self.step(x, y, new_dir) {
+                    Some(pos) => pos,
+                    None => continue,
+                };
+                let next_index = self.index(nx, ny)?;
+                if self.blocked[next_index] && next_index != end_index {
+                    continue;
+                }
+                let mut new_cost = cost + STEP_COST + self.pass_cost(x, y, dir, new_dir, source);
+                if new_dir != dir {
+                    new_cost += BEND_COST;
+                }
+                if next_index == end_index {
+                    new_cost += self.pass_cost(nx, ny, new_dir, RIGHT, source);
+                    if new_dir != RIGHT {
+                        new_cost += BEND_COST;
+                    }
+                }
+                let next = state(next_index, new_dir);
+                if costs.get(&next).is_none_or(|&old| new_cost < old) {
+                    costs.insert(next, new_cost);
+                    came_from.insert(next, current);
+                    open.push(Reverse((new_cost + heuristic(nx, ny), new_cost, nx, ny, new_dir)));
+                }
+            }
+        }
+        None
+    }
+
+    /// Three segment elbow through the midpoint, used when no free path exists.
+    pub fn elbow(start: (usize, usize), end: (usize, usize)) -> Vec<(usize, usize)> {
+        let mid_x = (start.0 + end.0) / 2;
+        let mut path = Vec::new();
+        let step_towards = |from: usize, to: usize| if from < to { from + 1 } else { from - 1 };
+        let (mut x, mut y) = start;
+        path.push((x, y));
+        while x != mid_x {
+            x = step_towards(x, mid_x);
+            path.push((x, y));
+        }
+        while y != end.1 {
+            y = step_towards(y, end.1);
+            path.push((x, y));
+        }
+        while x != end.0 {
+            x = step_towards(x, end.0);
+            path.push((x, y));
+        }
+        path
+    }
+
+    /// Records a routed path so later wires avoid its lane and junctions get the right glyph.
+    pub fn add_wire(&mut self, path: &[(usize, usize)], color: [u8; 3], source: usize) {
+        for (i, &(x, y)) in path.iter().enumerate() {
+            let mut mask = 0;
+            mask |= if i == 0 { LEFT } else { direction_between(path[i - 1], (x, y)) };
+            mask |= if i == path.len() - 1 { RIGHT } else { direction_between(path[i + 1], (x, y)) };
+            let cell = self.cells.entry((x, y)).or_insert(WireCell {
+                mask: 0,
+                color,
+                horizontal_source: None,
+                vertical_source: None,
+            });
+            cell.mask |= mask;
+            if mask & (LEFT | RIGHT) != 0 {
+                cell.horizontal_source.get_or_insert(source);
+            }
+            if mask & (UP | DOWN) != 0 {
+                cell.vertical_source.get_or_insert(source);
+            }
+        }
+    }
+}
+
+/// The side of `to` that `from` lies on.
+fn direction_between(from: (usize, usize), to: (usize, usize)) -> u8 {
+    if from.0 < to.0 {
+        LEFT
+    } else if from.0 > to.0 {
+        RIGHT
+    } else if from.1 < to.1 {
+        UP
+    } else {
+        DOWN
+    }
+}
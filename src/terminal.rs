@@ -0,0 +1,304 @@
+use std::io::{self, IsTerminal, Read, Write};
+use std::panic;
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(unix)]
+use std::sync::{Arc, Mutex};
+
+pub const CLEAR_SCREEN: &str = "\x1B[2J\x1B[H";
+const ENTER_ALTERNATE_SCREEN: &str = "\x1B[?1049h";
+const LEAVE_ALTERNATE_SCREEN: &str = "\x1B[?1049l";
+// button presses, drags and releases reported in the SGR format
+const ENABLE_MOUSE: &str = "\x1B[?1000h\x1B[?1002h\x1B[?1006h";
+const DISABLE_MOUSE: &str = "\x1B[?1006l\x1B[?1002l\x1B[?1000l";
+
+// kept outside `Terminal` so the panic hook can put the terminal back too
+#[cfg(unix)]
+static ORIGINAL_TERMIOS: Mutex<Option<libc::termios>> = Mutex::new(None);
+static ALTERNATE_SCREEN: AtomicBool = AtomicBool::new(false);
+static MOUSE: AtomicBool = AtomicBool::new(false);
+
+/// Leaves the alternate screen and raw mode, safe to call more than once.
+pub fn restore_terminal() {
+    if MOUSE.swap(false, Ordering::SeqCst) {
+        print!("{}", DISABLE_MOUSE);
+    }
+    if ALTERNATE_SCREEN.swap(false, Ordering::SeqCst) {
+        print!("{}", LEAVE_ALTERNATE_SCREEN);
+        let _ = io::stdout().flush();
+    }
+    #[cfg(unix)]
+    if let Some(termios) = ORIGINAL_TERMIOS.lock().map_or(None, |mut x| x.take()) {
+        unsafe {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &termios);
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+#[derive(PartialEq)]
+pub enum Key {
+    Char(char),
+    Enter,
+    Backspace,
+    Delete,
+    Left,
+    Right,
+    Up,
+    Down,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Escape,
+    Interrupt,
+    Eof,
+}
+
+#[derive(Clone, Copy)]
+#[derive(PartialEq)]
+pub enum MouseKind {
+    Press,
+    Drag,
+    Release,
+    ScrollUp,
+    ScrollDown,
+}
+
+// positions are 1-based like the cursor positions used for drawing
+#[derive(Clone, Copy)]
+pub struct Mouse {
+    pub kind: MouseKind,
+    pub x: usize,
+    pub y: usize,
+}
+
+pub enum Event {
+    Key(Key),
+    Mouse(Mouse),
+    Resize,
+}
+
+/// Switches to the alternate screen and raw mode while alive so keys arrive one by one, and reports window resizes.
+pub struct Terminal {
+    #[cfg(unix)]
+    resized: Arc<AtomicBool>,
+    #[cfg(unix)]
+    terminated: Arc<AtomicBool>,
+    pending: Vec<u8>,
+}
+impl Terminal {
+    #[cfg(unix)]
+    pub fn new() -> Self {
+        let resized = Arc::new(AtomicBool::new(false));
+        let terminated = Arc::new(AtomicBool::new(false));
+        let _ = signal_hook::flag::register(signal_hook::consts::SIGWINCH, Arc::clone(&resized));
+        for signal in [signal_hook::consts::SIGINT, signal_hook::consts::SIGTERM, signal_hook::consts::SIGHUP] {
+            let _ = signal_hook::flag::register(signal, Arc::clone(&terminated));
+        }
+        let terminal = Self {
+            resized,
+            terminated,
+            pending: Vec::new(),
+        };
+        Self::enter_alternate_screen();
+        terminal.enable_raw_mode();
+        terminal
+    }
+
+    #[cfg(not(unix))]
+    pub fn new() -> Self {
+        Self::enter_alternate_screen();
+        Self {
+            pending: Vec::new(),
+        }
+    }
+
+    fn enter_alternate_screen() {
+        if !io::stdout().is_terminal() || ALTERNATE_SCREEN.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        print!("{}", ENTER_ALTERNATE_SCREEN);
+        let _ = io::stdout().flush();
+        let previous_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            restore_terminal();
+            previous_hook(info);
+        }));
+    }
+
+    /// Turns mouse reporting on or off, only left button presses, drags and the wheel are read.
+    pub fn set_mouse(&mut self, enabled: bool) {
+        if MOUSE.swap(enabled, Ordering::SeqCst) != enabled {
+            print!("{}", if enabled { ENABLE_MOUSE } else { DISABLE_MOUSE });
+            let _ = io::stdout().flush();
+        }
+    }
+
+    #[cfg(unix)]
+    fn enable_raw_mode(&self) {
+        unsafe {
+            if libc::isatty(libc::STDIN_FILENO) == 0 {
+                return;
+            }
+            let mut termios: libc::termios = std::mem::zeroed();
+            if libc::tcgetattr(libc::STDIN_FILENO, &mut termios) != 0 {
+                return;
+            }
+            if let Ok(mut original) = ORIGINAL_TERMIOS.lock() {
+                original.get_or_insert(termios);
+            }
+            termios.c_lflag &= !(libc::ICANON | libc::ECHO | libc::ISIG | libc::IEXTEN);
+            termios.c_iflag &= !(libc::IXON | libc::ICRNL);
+            termios.c_cc[libc::VMIN] = 1;
+            termios.c_cc[libc::VTIME] = 0;
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &termios);
+        }
+    }
+
+    // waits up to `timeout_ms` for stdin, false on timeout or when a signal interrupted the wait
+    #[cfg(unix)]
+    fn wait_for_input(&self, timeout_ms: i32) -> bool {
+        let mut fds = libc::pollfd { fd: libc::STDIN_FILENO, events: libc::POLLIN, revents: 0 };
+        unsafe { libc::poll(&mut fds, 1, timeout_ms) > 0 }
+    }
+
+    #[cfg(not(unix))]
+    fn wait_for_input(&self, _timeout_ms: i32) -> bool {
+        true
+    }
+
+    fn fill(&mut self) -> bool {
+        let mut buffer = [0u8; 64];
+        match io::stdin().read(&mut buffer) {
+            Ok(0) | Err(_) => false,
+            Ok(n) => {
+                self.pending.extend_from_slice(&buffer[..n]);
+                true
+            }
+        }
+    }
+
+    /// Blocks until a key is pressed, the mouse is used or the window is resized, `None` once stdin is closed.
+    pub fn read_event(&mut self) -> Option<Event> {
+        loop {
+            if let Some(event) = self.parse_event() {
+                return Some(event);
+            }
+            #[cfg(unix)]
+            if self.terminated.load(Ordering::Relaxed) {
+                return None;
+            }
+            #[cfg(unix)]
+            if self.resized.swap(false, Ordering::Relaxed) {
+                return Some(Event::Resize);
+            }
+            if !self.wait_for_input(100) {
+                continue;
+            }
+            if !self.fill() {
+                return None;
+            }
+        }
+    }
+
+    fn parse_event(&mut self) -> Option<Event> {
+        let first = *self.pending.first()?;
+        if first == 0x1B {
+            // give the rest of an escape sequence a moment to arrive before treating it as a lone escape
+            if self.pending.len() == 1 && self.wait_for_input(30) {
+                self.fill();
+            }
+            return self.parse_escape();
+        }
+        let (key, len) = match first {
+            b'\r' | b'\n' => (Key::Enter, 1),
+            0x7F | 0x08 => (Key::Backspace, 1),
+            0x03 => (Key::Interrupt, 1),
+            0x04 => (Key::Eof, 1),
+            0x01 => (Key::Home, 1),
+            0x05 => (Key::End, 1),
+            _ => {
+                let len = match first {
+                    0xF0..=0xFF => 4,
+                    0xE0..=0xEF => 3,
+                    0xC0..=0xDF => 2,
+                    _ => 1,
+                };
+                if self.pending.len() < len {
+                    return None;
+                }
+                match std::str::from_utf8(&self.pending[..len]).ok().and_then(|x| x.chars().next()) {
+                    Some(c) if !c.is_control() => (Key::Char(c), len),
+                    _ => {
+                        self.pending.drain(..len);
+                        return self.parse_event();
+                    }
+                }
+            }
+        };
+        self.pending.drain(..len);
+        // a carriage return followed by a newline is one enter
+        if first == b'\r' && self.pending.first() == Some(&b'\n') {
+            self.pending.remove(0);
+        }
+        Some(Event::Key(key))
+    }
+
+    fn parse_escape(&mut self) -> Option<Event> {
+        if self.pending.len() < 3 || (self.pending[1] != b'[' && self.pending[1] != b'O') {
+            self.pending.remove(0);
+            return Some(Event::Key(Key::Escape));
+        }
+        let end = match self.pending[2..].iter().position(|&b| (0x40..=0x7E).contains(&b)) {
+            Some(position) => position + 3,
+            None => {
+                self.pending.clear();
+                return Some(Event::Key(Key::Escape));
+            }
+        };
+        let sequence: Vec<u8> = self.pending.drain(..end).collect();
+        if sequence[2] == b'<' {
+            return match Self::parse_mouse(&sequence[3..]) {
+                Some(mouse) => Some(Event::Mouse(mouse)),
+                None => self.parse_event(),
+            };
+        }
+        let key = match &sequence[2..] {
+            b"A" => Key::Up,
+            b"B" => Key::Down,
+            b"C" => Key::Right,
+            b"D" => Key::Left,
+            b"H" | b"1~" | b"7~" => Key::Home,
+            b"F" | b"4~" | b"8~" => Key::End,
+            b"3~" => Key::Delete,
+            b"5~" => Key::PageUp,
+            b"6~" => Key::PageDown,
+            _ => Key::Escape,
+        };
+        Some(Event::Key(key))
+    }
+
+    // `button;x;y` followed by M for a press or m for a release
+    fn parse_mouse(sequence: &[u8]) -> Option<Mouse> {
+        let (last, fields) = sequence.split_last()?;
+        let fields: Vec<usize> = std::str::from_utf8(fields).ok()?.split(';').map(|x| x.parse().ok()).collect::<Option<_>>()?;
+        let [button, x, y] = fields[..] else {
+            return None;
+        };
+        let kind = match button {
+            64 => MouseKind::ScrollUp,
+            65 => MouseKind::ScrollDown,
+            32 => MouseKind::Drag,
+            0 if *last == b'm' => MouseKind::Release,
+            0 => MouseKind::Press,
+            // other buttons and modifiers are not used
+            _ => return None,
+        };
+        Some(Mouse { kind, x, y })
+    }
+}
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        restore_terminal();
+    }
+}
@@ -0,0 +1,559 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::rc::Rc;
+use crate::editor::*;
+use crate::memory_backend::{MemoryBackend, Screen};
+use crate::palette::{fuzzy_score, Palette};
+use crate::theme::Theme;
+
+// run with UPDATE_SNAPSHOTS=1 to write the current output as the expected one
+fn assert_snapshot(name: &str, actual: &str) {
+    let path = format!("{}/snapshots/{}.snap", env!("CARGO_MANIFEST_DIR"), name);
+    if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path).unwrap_or_default();
+    assert!(expected == actual, "snapshot {} differs, expected:\n{}\nactual:\n{}", name, expected, actual);
+}
+
+// an editor drawing into a `w` by `h` screen in memory
+fn new_editor(w: usize, h: usize) -> (Editor, Rc<RefCell<Screen>>) {
+    let backend = MemoryBackend::new(w, h);
+    let screen = backend.get_screen();
+    let mut editor = Editor::new();
+    editor.set_backend(Box::new(backend));
+    editor.update_size();
+    (editor, screen)
+}
+
+fn render(editor: &mut Editor, screen: &Rc<RefCell<Screen>>) -> String {
+    editor.draw();
+    let snapshot = screen.borrow().get_snapshot();
+    snapshot
+}
+
+fn add_node(editor: &mut Editor, template: MyNodeTemplate, x: usize, y: usize) {
+    let mut node = template.create_node();
+    node.x = x;
+    node.y = y;
+    editor.add_node(node);
+}
+
+#[test]
+fn single_node() {
+    let (mut editor, screen) = new_editor(70, 20);
+    add_node(&mut editor, MyNodeTemplate::RepeatString, 4, 3);
+    assert_snapshot("single_node", &render(&mut editor, &screen));
+}
+
+#[test]
+fn connected_nodes() {
+    let (mut editor, screen) = new_editor(110, 20);
+    add_node(&mut editor, MyNodeTemplate::NewNumber, 3, 3);
+    add_node(&mut editor, MyNodeTemplate::RepeatString, 40, 8);
+    editor.connect_nodes(NodeId(1), NodeId(2), 1);
+    assert_snapshot("connected_nodes", &render(&mut editor, &screen));
+}
+
+#[test]
+fn wire_around_node() {
+    let (mut editor, screen) = new_editor(110, 20);
+    add_node(&mut editor, MyNodeTemplate::NewNumber, 3, 6);
+    add_node(&mut editor, MyNodeTemplate::NewNumber, 22, 5);
+    add_node(&mut editor, MyNodeTemplate::RepeatString, 42, 6);
+    editor.connect_nodes(NodeId(1), NodeId(3), 1);
+    assert_snapshot("wire_around_node", &render(&mut editor, &screen));
+}
+
+#[test]
+fn inspector_and_status_bar() {
+    let (mut editor, screen) = new_editor(100, 24);
+    add_node(&mut editor, MyNodeTemplate::NewNumber, 3, 3);
+    add_node(&mut editor, MyNodeTemplate::RepeatString, 30, 3);
+    editor.connect_nodes(NodeId(1), NodeId(2), 1);
+    editor.run_command("inspect 2");
+    editor.run_command("edit 0 a long value\\nover two lines");
+    assert_snapshot("inspector_and_status_bar", &render(&mut editor, &screen));
+}
+
+#[test]
+fn ascii_theme_without_color() {
+    let (mut editor, screen) = new_editor(70, 20);
+    add_node(&mut editor, MyNodeTemplate::NewNumber, 3, 3);
+    add_node(&mut editor, MyNodeTemplate::RepeatString, 30, 3);
+    editor.connect_nodes(NodeId(1), NodeId(2), 1);
+    editor.run_command("theme ascii");
+    editor.run_command("color off");
+    assert_snapshot("ascii_theme_without_color", &render(&mut editor, &screen));
+}
+
+#[test]
+fn wide_characters() {
+    let (mut editor, screen) = new_editor(80, 20);
+    add_node(&mut editor, MyNodeTemplate::RepeatString, 3, 3);
+    editor.run_command("inspect 1");
+    editor.run_command("edit 0 日本語テキスト");
+    assert_snapshot("wide_characters", &render(&mut editor, &screen));
+}
+
+#[test]
+fn tiny_terminal() {
+    let (mut editor, screen) = new_editor(12, 6);
+    add_node(&mut editor, MyNodeTemplate::RepeatString, 3, 3);
+    assert_snapshot("tiny_terminal", &render(&mut editor, &screen));
+}
+
+#[test]
+fn narrow_terminal_keeps_the_canvas() {
+    let (mut editor, screen) = new_editor(50, 16);
+    add_node(&mut editor, MyNodeTemplate::NewNumber, 3, 3);
+    assert_snapshot("narrow_terminal", &render(&mut editor, &screen));
+}
+
+#[test]
+fn palette_ranks_and_picks_nodes() {
+    // gaps inside the match cost twice what is skipped before it
+    assert_eq!(fuzzy_score("ee", "Repeat string"), Some(3));
+    assert_eq!(fuzzy_score("ee", "Reroute"), Some(9));
+    assert_eq!(fuzzy_score("EE", "New number"), Some(13));
+    assert_eq!(fuzzy_score("ee", "Delay"), None);
+
+    let mut palette = Palette::default();
+    palette.set_query("e");
+    let titles: Vec<String> = palette.get_matches("e").into_iter().map(|(_, title)| title).collect();
+    assert_eq!(titles, ["New number", "Repeat string", "Delay", "Reroute"]);
+    palette.move_highlight(3);
+    assert_eq!(palette.get_choice("e").as_deref(), Some("Reroute"));
+    // another query starts from its best match, an exact title is taken as it is
+    assert_eq!(palette.get_choice("re").as_deref(), Some("Repeat string"));
+    assert_eq!(palette.get_choice("Delay").as_deref(), Some("Delay"));
+    assert_eq!(palette.get_choice("xyz"), None);
+
+    let (mut editor, _) = new_editor(90, 24);
+    editor.run_command("add_node xyz");
+    assert!(editor.get_output_lines().last().unwrap().starts_with("Unknown node, available nodes: "));
+    assert!(editor.get_status_text().contains("0 nodes"));
+}
+
+#[test]
+fn selection_duplicate_and_align() {
+    let (mut editor, screen) = new_editor(90, 24);
+    add_node(&mut editor, MyNodeTemplate::NewNumber, 3, 3);
+    add_node(&mut editor, MyNodeTemplate::RepeatString, 25, 5);
+    editor.connect_nodes(NodeId(1), NodeId(2), 1);
+    editor.run_command("select all");
+    editor.run_command("duplicate");
+    editor.run_command("align top");
+    editor.run_command("set number 7");
+    editor.run_command("out clear");
+    assert_snapshot("selection_duplicate_and_align", &render(&mut editor, &screen));
+}
+
+#[test]
+fn copy_and_paste_between_sessions() {
+    let (mut editor, screen) = new_editor(90, 24);
+    add_node(&mut editor, MyNodeTemplate::NewNumber, 3, 3);
+    add_node(&mut editor, MyNodeTemplate::RepeatString, 25, 3);
+    editor.connect_nodes(NodeId(1), NodeId(2), 1);
+    editor.run_command("select 2");
+    editor.run_command("copy");
+    editor.run_command("paste keep");
+    editor.run_command("paste");
+    editor.run_command("out clear");
+    assert_snapshot("copy_and_paste", &render(&mut editor, &screen));
+
+    // the source of the kept connection does not exist in another session
+    let (mut other, _) = new_editor(90, 24);
+    other.run_command("paste keep");
+    assert!(other.get_status_text().contains("1 nodes, 0 connections"));
+
+    // a clipboard edited by hand, one input short and with the types mixed up
+    let text = "[[nodes]]\nid = 1\ntitle = \"Repeat string\"\nnode_type = \"RepeatString\"\nx = 3\ny = 12\nw = 0\n\n\
+        [[nodes.inputs]]\ninput_type = \"Isize\"\nlabel = \"string\"\n\n[nodes.inputs.value.Isize]\nvalue = 2\n\n\
+        [nodes.output]\nconnected_ids = []\noutput_type = \"Isize\"\n";
+    fs::write(crate::clipboard::get_path(), text).unwrap();
+    other.run_command("paste");
+    assert!(other.check().is_empty());
+    other.run_command("calc_out 2");
+    assert_eq!(other.get_output_lines().last().unwrap(), "#2: ");
+    fs::remove_file(crate::clipboard::get_path()).unwrap();
+}
+
+#[test]
+fn arrange_in_columns() {
+    let (mut editor, screen) = new_editor(100, 30);
+    add_node(&mut editor, MyNodeTemplate::RepeatString, 2, 2);
+    add_node(&mut editor, MyNodeTemplate::NewNumber, 2, 2);
+    add_node(&mut editor, MyNodeTemplate::RepeatString, 2, 2);
+    add_node(&mut editor, MyNodeTemplate::NewNumber, 2, 2);
+    add_node(&mut editor, MyNodeTemplate::RepeatString, 2, 2);
+    editor.connect_nodes(NodeId(4), NodeId(1), 1);
+    editor.connect_nodes(NodeId(2), NodeId(3), 1);
+    editor.connect_nodes(NodeId(2), NodeId(5), 1);
+    editor.connect_nodes(NodeId(3), NodeId(5), 0);
+    editor.run_command("arrange");
+    assert_snapshot("arrange_in_columns", &render(&mut editor, &screen));
+}
+
+#[test]
+fn arranged_selection_does_not_cover_other_nodes() {
+    let (mut editor, _) = new_editor(100, 30);
+    add_node(&mut editor, MyNodeTemplate::NewNumber, 24, 3);
+    add_node(&mut editor, MyNodeTemplate::NewNumber, 3, 3);
+    add_node(&mut editor, MyNodeTemplate::RepeatString, 3, 12);
+    editor.connect_nodes(NodeId(2), NodeId(3), 1);
+    editor.run_command("select 2 3");
+    editor.run_command("arrange selection");
+    // the second column would start on node 1, the later ids win in get_node_at
+    for (x, y) in [(24, 3), (30, 5), (39, 7)] {
+        assert_eq!(editor.get_node_at(x, y), Some(NodeId(1)));
+    }
+}
+
+#[test]
+fn grid_collisions_and_distribute() {
+    let (mut editor, screen) = new_editor(100, 30);
+    add_node(&mut editor, MyNodeTemplate::NewNumber, 3, 3);
+    add_node(&mut editor, MyNodeTemplate::NewNumber, 40, 14);
+    add_node(&mut editor, MyNodeTemplate::NewNumber, 30, 14);
+    add_node(&mut editor, MyNodeTemplate::NewNumber, 50, 3);
+    editor.run_command("grid 4");
+    editor.run_command("pos 1 3 3");
+    // lands on the grid below node 1 instead of on top of it
+    editor.run_command("pos 2 6 4");
+    editor.run_command("select 1 3 4");
+    editor.run_command("distribute h");
+    assert_snapshot("grid_collisions_and_distribute", &render(&mut editor, &screen));
+
+    // aligning two nodes in a column puts the lower one below the upper one instead of on it
+    let (mut editor, _) = new_editor(100, 30);
+    add_node(&mut editor, MyNodeTemplate::NewNumber, 3, 3);
+    add_node(&mut editor, MyNodeTemplate::NewNumber, 3, 12);
+    editor.run_command("select all");
+    editor.run_command("align top");
+    assert_eq!(editor.get_node_at(4, 7), Some(NodeId(1)));
+    assert_eq!(editor.get_node_at(4, 8), Some(NodeId(2)));
+}
+
+#[test]
+fn frames_and_notes() {
+    let (mut editor, screen) = new_editor(100, 30);
+    add_node(&mut editor, MyNodeTemplate::NewNumber, 4, 4);
+    add_node(&mut editor, MyNodeTemplate::RepeatString, 26, 5);
+    add_node(&mut editor, MyNodeTemplate::NewNumber, 50, 4);
+    editor.connect_nodes(NodeId(1), NodeId(2), 1);
+    editor.run_command("select 1 2");
+    editor.run_command("add_frame selection Inputs");
+    editor.run_command("add_note 36 18 check the\\ncount");
+    // the frame takes nodes 1 and 2 along, node 3 and the note stay
+    editor.run_command("pos_annotation 1 3 6");
+    editor.run_command("select none");
+    editor.run_command("out clear");
+    assert_snapshot("frames_and_notes", &render(&mut editor, &screen));
+}
+
+#[test]
+fn reroute_takes_the_first_connected_type() {
+    let (mut editor, screen) = new_editor(90, 24);
+    add_node(&mut editor, MyNodeTemplate::NewNumber, 3, 3);
+    add_node(&mut editor, MyNodeTemplate::Reroute, 30, 12);
+    add_node(&mut editor, MyNodeTemplate::RepeatString, 50, 3);
+    editor.connect_nodes(NodeId(2), NodeId(3), 1);
+    editor.connect_nodes(NodeId(1), NodeId(2), 0);
+    // fixed to isize by the first connection, so a string input is refused
+    editor.connect_nodes(NodeId(2), NodeId(3), 0);
+    editor.run_command("set_val 1 0 3");
+    editor.run_command("inspect 3");
+    editor.run_command("edit 0 ab");
+    // the reroute passes the number through
+    editor.run_command("calc_out 3");
+    editor.run_command("out clear");
+    assert_snapshot("reroute", &render(&mut editor, &screen));
+}
+
+#[test]
+fn renamed_node_and_input() {
+    let (mut editor, screen) = new_editor(90, 24);
+    add_node(&mut editor, MyNodeTemplate::NewNumber, 3, 3);
+    add_node(&mut editor, MyNodeTemplate::RepeatString, 30, 3);
+    editor.connect_nodes(NodeId(1), NodeId(2), 1);
+    editor.run_command("rename 1 Copies");
+    editor.run_command("rename 2 Banner\ttext");
+    editor.run_command("rename_input 2 0 word");
+    editor.run_command("inspect 2");
+    // the custom label works like the template's
+    editor.run_command("set word hi");
+    editor.run_command("out clear");
+    assert_snapshot("renamed_node_and_input", &render(&mut editor, &screen));
+}
+
+#[test]
+fn explicit_ids_and_renumber() {
+    let (mut editor, screen) = new_editor(90, 24);
+    add_node(&mut editor, MyNodeTemplate::NewNumber, 3, 3);
+    add_node(&mut editor, MyNodeTemplate::NewNumber, 3, 9);
+    editor.run_command("del_node 1");
+    editor.run_command("add_node_as 7 Repeat string");
+    editor.run_command("pos 7 30 3");
+    editor.run_command("add_node_as 7 New number");
+    editor.run_command("con 2 7 1");
+    editor.run_command("renumber");
+    // the next id follows the compacted ones
+    editor.run_command("add_node New number");
+    editor.run_command("pos 3 36 11");
+    editor.run_command("select 2");
+    // taking id 7 twice only logged an error
+    editor.run_command("out clear");
+    assert_snapshot("explicit_ids_and_renumber", &render(&mut editor, &screen));
+
+    // 0 is not an id, and text is not read as one
+    editor.run_command("add_node_as 0 New number");
+    editor.run_command("calc_out oops");
+    editor.run_command("watch nope");
+    editor.run_command("con foo 1 0");
+    assert_eq!(editor.get_output_lines(), [
+        "Node ids start at 1.",
+        "Unknown node id oops.",
+        "Unknown node id nope.",
+        "Unknown node id or input index.",
+    ]);
+}
+
+#[test]
+fn check_and_repair() {
+    let (mut editor, screen) = new_editor(90, 24);
+    add_node(&mut editor, MyNodeTemplate::NewNumber, 3, 3);
+    add_node(&mut editor, MyNodeTemplate::RepeatString, 30, 3);
+    add_node(&mut editor, MyNodeTemplate::RepeatString, 30, 10);
+    // connecting and disconnecting leaves no trace in the source's output
+    editor.run_command("con 1 2 1");
+    editor.run_command("con 1 2 1");
+    assert!(editor.check().is_empty());
+
+    editor.run_command("con 1 3 1");
+    editor.set_input_connection(NodeId(2), 1, Some(NodeId(9)));
+    editor.set_input_connection(NodeId(3), 0, Some(NodeId(1)));
+    assert_eq!(editor.check(), [
+        "node 1 lists 1 connections to node 3 but 2 inputs are connected.",
+        "node 2 input 1 is connected to missing node 9.",
+        "node 3 input 0 takes string but node 1 gives isize.",
+    ]);
+    assert_eq!(editor.repair().len(), 3);
+    assert!(editor.check().is_empty());
+    assert_snapshot("check_and_repair", &render(&mut editor, &screen));
+}
+
+#[test]
+fn cycles_are_refused_and_found() {
+    let (mut editor, _) = new_editor(90, 24);
+    add_node(&mut editor, MyNodeTemplate::RepeatString, 3, 3);
+    add_node(&mut editor, MyNodeTemplate::RepeatString, 30, 3);
+    add_node(&mut editor, MyNodeTemplate::RepeatString, 60, 3);
+    editor.run_command("con 1 2 0");
+    editor.run_command("con 2 3 0");
+    assert_eq!(editor.get_cycle_path(NodeId(3), NodeId(1), 0), Some(vec![NodeId(1), NodeId(2), NodeId(3), NodeId(1)]));
+    assert_eq!(editor.get_cycle_path(NodeId(2), NodeId(2), 0), Some(vec![NodeId(2), NodeId(2)]));
+    assert_eq!(editor.get_cycle_path(NodeId(1), NodeId(3), 0), None);
+    editor.run_command("con 3 1 0");
+    assert!(editor.get_cycles().is_empty());
+    assert_eq!(editor.get_output_lines().last().unwrap(), "Connecting 3 to 1 would make a cycle: 1 → 2 → 3 → 1.");
+
+    // a source of the wrong type is refused without dropping the connection it would replace
+    add_node(&mut editor, MyNodeTemplate::NewNumber, 3, 12);
+    editor.run_command("con 4 2 0");
+    assert_eq!(editor.get_output_lines().last().unwrap(), "Node 4 gives isize but input 0 of node 2 takes string.");
+    assert!(editor.get_status_text().contains("2 connections"));
+
+    // a cycle can only come from a file written by hand
+    editor.set_input_connection(NodeId(1), 0, Some(NodeId(3)));
+    editor.set_input_connection(NodeId(3), 1, Some(NodeId(3)));
+    assert_eq!(editor.get_cycles(), [
+        vec![NodeId(1), NodeId(2), NodeId(3), NodeId(1)],
+        vec![NodeId(3), NodeId(3)],
+    ]);
+}
+
+#[test]
+fn delay_breaks_a_loop() {
+    let (mut editor, _) = new_editor(90, 24);
+    add_node(&mut editor, MyNodeTemplate::Delay, 3, 3);
+    add_node(&mut editor, MyNodeTemplate::RepeatString, 30, 3);
+    editor.run_command("con 1 2 0");
+    editor.run_command("con 2 1 0");
+    editor.run_command("set_val 1 1 ab");
+    editor.run_command("set_val 2 1 2");
+    assert!(editor.get_cycles().is_empty());
+    // only the delay's first input is read a step late
+    assert_eq!(editor.get_cycle_path(NodeId(2), NodeId(1), 1), Some(vec![NodeId(1), NodeId(2), NodeId(1)]));
+
+    assert_eq!(editor.evaluate_node(NodeId(2)).get_print_text(), "abab");
+    editor.run_command("step");
+    assert_eq!(editor.evaluate_node(NodeId(2)).get_print_text(), "abababab");
+    editor.run_command("step 2");
+    assert_eq!(editor.evaluate_node(NodeId(1)).get_print_text(), "ab".repeat(8));
+}
+
+#[test]
+fn calc_all_and_watch() {
+    let (mut editor, _) = new_editor(90, 24);
+    add_node(&mut editor, MyNodeTemplate::NewNumber, 3, 3);
+    add_node(&mut editor, MyNodeTemplate::RepeatString, 30, 3);
+    add_node(&mut editor, MyNodeTemplate::RepeatString, 30, 10);
+    add_node(&mut editor, MyNodeTemplate::NewNumber, 3, 10);
+    editor.run_command("con 4 2 1");
+    editor.run_command("con 1 3 1");
+    editor.run_command("set_val 1 0 2");
+    editor.run_command("set_val 2 0 ab");
+    editor.run_command("set_val 3 0 c");
+    editor.run_command("set_val 4 0 1");
+    assert_eq!(editor.get_evaluation_order(), [NodeId(1), NodeId(3), NodeId(4), NodeId(2)]);
+    editor.run_command("out clear");
+    editor.run_command("calc_all");
+    assert_eq!(editor.get_output_lines(), ["#3: cc", "#2: ab"]);
+
+    editor.run_command("out clear");
+    editor.run_command("watch 2");
+    editor.run_command("watch 1");
+    editor.run_command("set_val 4 0 3");
+    // scrolling the output does not change the graph
+    editor.run_command("out scroll 1");
+    editor.run_command("unwatch 2");
+    editor.run_command("set_val 1 0 5");
+    assert_eq!(editor.get_output_lines(), [
+        "#2: ab", "#1: 2",
+        "#1: 2", "#2: ababab",
+        "Node 2 is no longer watched.",
+        "#1: 5",
+    ]);
+}
+
+#[test]
+fn dangling_connections_do_not_panic() {
+    let (mut editor, _) = new_editor(90, 24);
+    add_node(&mut editor, MyNodeTemplate::NewNumber, 3, 3);
+    add_node(&mut editor, MyNodeTemplate::RepeatString, 30, 3);
+    editor.run_command("con 1 2 1");
+    editor.set_input_connection(NodeId(2), 0, Some(NodeId(9)));
+    editor.run_command("renumber");
+    assert!(editor.check().is_empty());
+    editor.set_input_connection(NodeId(2), 0, Some(NodeId(9)));
+    editor.run_command("del_node 2");
+    assert!(editor.check().is_empty());
+
+    add_node(&mut editor, MyNodeTemplate::Reroute, 30, 12);
+    add_node(&mut editor, MyNodeTemplate::Reroute, 40, 12);
+    editor.run_command("con 3 4 0");
+    editor.set_input_connection(NodeId(4), 0, Some(NodeId(9)));
+    editor.run_command("con 1 3 0");
+    assert!(editor.get_status_text().contains("2 connections"));
+}
+
+#[test]
+fn wire_to_a_far_node() {
+    let (mut editor, screen) = new_editor(70, 20);
+    add_node(&mut editor, MyNodeTemplate::NewNumber, 3, 3);
+    add_node(&mut editor, MyNodeTemplate::RepeatString, 3000, 3000);
+    editor.connect_nodes(NodeId(1), NodeId(2), 1);
+    assert_snapshot("wire_to_a_far_node", &render(&mut editor, &screen));
+}
+
+#[test]
+fn load_file_from_before_the_format_header() {
+    // the old layout field by field: nodes, outputs cache, current id, autosave, w and h
+    type OldInput = (usize, MyDataType, [u8; 3], String, MyValueType);
+    type OldNode = (usize, String, MyNodeTemplate, Vec<OldInput>, (Vec<usize>, MyDataType, [u8; 3]), usize, usize, usize);
+    let number = (1, "New number".to_string(), MyNodeTemplate::NewNumber,
+        vec![(0, MyDataType::Isize, [0; 3], "number".to_string(), MyValueType::Isize { value: 3 })],
+        (vec![2], MyDataType::Isize, [0; 3]), 3, 3, 0);
+    let repeat = (2, "Repeat string".to_string(), MyNodeTemplate::RepeatString,
+        vec![
+            (0, MyDataType::String, [0; 3], "string".to_string(), MyValueType::String { value: "ab".to_string() }),
+            (1, MyDataType::Isize, [0; 3], "number".to_string(), MyValueType::Isize { value: 0 }),
+        ],
+        (vec![], MyDataType::String, [0; 3]), 30, 3, 0);
+    let nodes: HashMap<usize, OldNode> = [(1, number), (2, repeat)].into_iter().collect();
+    let old = (nodes, HashMap::<usize, MyValueType>::new(), 2usize, false, 0usize, 0usize);
+    let path = env::temp_dir().join("asgore_node_editor_old_format.ane");
+    fs::write(&path, bincode::serialize(&old).unwrap()).unwrap();
+
+    let mut editor = Editor::load(path.to_str().unwrap()).unwrap();
+    assert!(editor.check().is_empty());
+    assert_eq!(editor.evaluate_node(NodeId(2)).get_print_text(), "ababab");
+
+    editor.save(path.to_str().unwrap()).unwrap();
+    let mut editor = Editor::load(path.to_str().unwrap()).unwrap();
+    assert_eq!(editor.evaluate_node(NodeId(2)).get_print_text(), "ababab");
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn loaded_nodes_get_the_inputs_of_their_template() {
+    type OldInput = (usize, MyDataType, [u8; 3], String, MyValueType);
+    type OldNode = (usize, String, MyNodeTemplate, Vec<OldInput>, (Vec<usize>, MyDataType, [u8; 3]), usize, usize, usize);
+    let repeat = (1, "Repeat string".to_string(), MyNodeTemplate::RepeatString,
+        vec![(0, MyDataType::String, [0; 3], "string".to_string(), MyValueType::String { value: "ab".to_string() })],
+        (vec![], MyDataType::String, [0; 3]), 3, 3, 0);
+    let delay = (2, "Delay".to_string(), MyNodeTemplate::Delay, vec![], (vec![], MyDataType::Any, [0; 3]), 30, 3, 0);
+    let nodes: HashMap<usize, OldNode> = [(1, repeat), (2, delay)].into_iter().collect();
+    let old = (nodes, HashMap::<usize, MyValueType>::new(), 2usize, false, 0usize, 0usize);
+    let path = env::temp_dir().join("asgore_node_editor_short_inputs.ane");
+    fs::write(&path, bincode::serialize(&old).unwrap()).unwrap();
+
+    let mut editor = Editor::load(path.to_str().unwrap()).unwrap();
+    fs::remove_file(path).unwrap();
+    assert!(editor.check().is_empty());
+    assert_eq!(editor.evaluate_node(NodeId(1)).get_print_text(), "");
+    editor.run_command("step");
+    editor.run_command("calc_out 2");
+}
+
+#[test]
+fn theme_file_keys_fall_back_to_unicode() {
+    let theme = Theme::from_text("name = \"mine\"\n[node]\ntedge = '~'\n").unwrap();
+    assert_eq!(theme.name, "mine");
+    assert_eq!((theme.node.tedge, theme.node.ledge), ('~', '║'));
+    assert_eq!(theme.container.tlcorner, '┌');
+    assert!(Theme::from_text("[node]\ntedge = 1\n").is_err());
+}
+
+#[test]
+fn failed_save_keeps_the_graph_modified() {
+    let (mut editor, _) = new_editor(90, 24);
+    editor.run_command("add_node New number");
+    let path = env::temp_dir().join("asgore_node_editor_no_such_dir").join("graph");
+    editor.run_command(&format!("save {}", path.to_str().unwrap()));
+    assert!(editor.get_status_text().starts_with("untitled (modified)"));
+    assert_eq!(editor.get_output_lines().last().unwrap(), &format!("the graph can not be saved to {}.ane.", path.to_str().unwrap()));
+}
+
+#[test]
+fn load_keeps_the_session() {
+    let (mut editor, _) = new_editor(90, 24);
+    add_node(&mut editor, MyNodeTemplate::NewNumber, 3, 3);
+    add_node(&mut editor, MyNodeTemplate::NewNumber, 3, 12);
+    let path = env::temp_dir().join("asgore_node_editor_keeps_session");
+    let path = path.to_str().unwrap();
+    editor.run_command("set_val 1 0 5");
+    editor.run_command(&format!("save {}", path));
+    editor.run_command("set_val 1 0 7");
+    editor.run_command("add_node New number");
+    editor.run_command("watch 1");
+    editor.run_command("watch 3");
+    editor.run_command("grid 4");
+    editor.run_command("out clear");
+    editor.run_command(&format!("load {}", path));
+    assert_eq!(editor.get_output_lines()[1], "No longer watching 3, the file has no such nodes.");
+    // the watched node is shown with its value in the loaded file, which is not modified
+    assert_eq!(editor.get_output_lines().last().unwrap(), "#1: 5");
+    assert!(editor.get_status_text().contains("(saved)"));
+    editor.run_command("watch");
+    assert_eq!(editor.get_output_lines().last().unwrap(), "Watching 1.");
+    // the grid is still on, so the node lands on a grid row
+    editor.run_command("pos 2 3 14");
+    assert_eq!(editor.get_node_at(5, 13), Some(NodeId(2)));
+    assert!(editor.get_node_at(4, 14).is_none());
+    fs::remove_file(format!("{}.ane", path)).unwrap();
+}
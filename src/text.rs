@@ -0,0 +1,86 @@
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+// a cluster is drawn in one or two cells, combining marks add nothing
+fn get_grapheme_width(grapheme: &str) -> usize {
+    grapheme.width().min(2)
+}
+
+/// Replaces control characters with visible stand-ins so user data can not move the cursor or send escapes.
+pub fn escape_control(text: &str) -> String {
+    text.chars().map(|c| match c {
+        '\n' => "⏎".to_string(),
+        '\t' => "→".to_string(),
+        c if c.is_control() => format!("\\x{:02x}", c as u32),
+        c => c.to_string(),
+    }).collect()
+}
+
+/// Like `escape_control` but keeps line breaks and turns tabs into spaces, for multi-line output.
+pub fn escape_control_lines(text: &str) -> String {
+    text.split('\n').map(|x| escape_control(&x.replace('\t', "    "))).collect::<Vec<String>>().join("\n")
+}
+
+/// Terminal columns taken by `text`.
+pub fn get_width(text: &str) -> usize {
+    text.graphemes(true).map(get_grapheme_width).sum()
+}
+
+/// The longest start of `text` that fits in `width` columns, never splitting a grapheme.
+pub fn truncate(text: &str, width: usize) -> String {
+    let mut result = String::new();
+    let mut used = 0;
+    for grapheme in text.graphemes(true) {
+        used += get_grapheme_width(grapheme);
+        if used > width {
+            break;
+        }
+        result += grapheme;
+    }
+    result
+}
+
+/// `text` truncated and then filled with spaces to exactly `width` columns.
+pub fn pad(text: &str, width: usize) -> String {
+    let text = truncate(text, width);
+    let padding = " ".repeat(width - get_width(&text));
+    text + &padding
+}
+
+/// Drops the first `columns` columns of `text`, a wide grapheme cut in half is replaced by a space.
+pub fn skip_columns(text: &str, columns: usize) -> String {
+    let mut result = String::new();
+    let mut skipped = 0;
+    for grapheme in text.graphemes(true) {
+        if skipped >= columns {
+            result += grapheme;
+            continue;
+        }
+        skipped += get_grapheme_width(grapheme);
+        if skipped > columns {
+            result += &" ".repeat(skipped - columns);
+        }
+    }
+    result
+}
+
+/// Breaks `text` into lines of at most `width` columns.
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    for line in text.split('\n') {
+        let mut current = String::new();
+        let mut used = 0;
+        for grapheme in line.graphemes(true) {
+            let grapheme_width = get_grapheme_width(grapheme);
+            // a grapheme wider than the whole line still gets a line of its own
+            if used + grapheme_width > width && !current.is_empty() {
+                lines.push(std::mem::take(&mut current));
+                used = 0;
+            }
+            current += grapheme;
+            used += grapheme_width;
+        }
+        lines.push(current);
+    }
+    lines
+}
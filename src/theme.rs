@@ -0,0 +1,219 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use crate::editor::MyDataType;
+use crate::router::{UP, DOWN, LEFT, RIGHT};
+
+const THEME_CONFIG_NAME: &str = "theme.toml";
+
+/// The theme file loaded on start, next to the executable.
+pub fn get_config_path() -> Option<PathBuf> {
+    Some(std::env::current_exe().ok()?.parent()?.join(THEME_CONFIG_NAME))
+}
+
+// copies every key of `theme` over `base`, going into tables that are in both
+fn merge(base: &mut toml::Table, theme: toml::Table) {
+    for (key, value) in theme {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(table)) => merge(base, table),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+#[derive(Serialize, Deserialize)]
+pub struct BorderSet {
+    pub trcorner: char,
+    pub tlcorner: char,
+    pub drcorner: char,
+    pub dlcorner: char,
+    pub tedge: char,
+    pub dedge: char,
+    pub redge: char,
+    pub ledge: char,
+}
+
+#[derive(Clone, Copy)]
+#[derive(Serialize, Deserialize)]
+pub struct SocketSet {
+    pub input: char,
+    pub output: char,
+    // the single glyph a reroute node is drawn as
+    pub reroute: char,
+}
+
+#[derive(Clone, Copy)]
+#[derive(Serialize, Deserialize)]
+pub struct WireSet {
+    pub hedge: char,
+    pub vedge: char,
+    pub trcorner: char,
+    pub tlcorner: char,
+    pub drcorner: char,
+    pub dlcorner: char,
+    pub rtee: char,
+    pub ltee: char,
+    pub ttee: char,
+    pub dtee: char,
+    pub cross: char,
+}
+impl WireSet {
+    // `mask` holds the router directions a wire cell is connected to
+    pub fn get_glyph(&self, mask: u8) -> char {
+        match mask {
+            m if m == UP | DOWN => self.vedge,
+            m if m == DOWN | RIGHT => self.tlcorner,
+            m if m == DOWN | LEFT => self.trcorner,
+            m if m == UP | RIGHT => self.dlcorner,
+            m if m == UP | LEFT => self.drcorner,
+            m if m == UP | DOWN | RIGHT => self.ltee,
+            m if m == UP | DOWN | LEFT => self.rtee,
+            m if m == DOWN | LEFT | RIGHT => self.ttee,
+            m if m == UP | LEFT | RIGHT => self.dtee,
+            m if m == UP | DOWN | LEFT | RIGHT => self.cross,
+            m if m & (LEFT | RIGHT) != 0 => self.hedge,
+            _ => self.vedge,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+#[derive(Serialize, Deserialize)]
+pub struct TypeColors {
+    pub isize: [u8; 3],
+    pub string: [u8; 3],
+    pub any: [u8; 3],
+}
+
+// socket shapes used instead of colours when colour is off
+#[derive(Clone, Copy)]
+#[derive(Serialize, Deserialize)]
+pub struct TypeGlyphs {
+    pub isize: char,
+    pub string: char,
+    pub any: char,
+}
+
+#[derive(Clone)]
+#[derive(Serialize, Deserialize)]
+pub struct Theme {
+    pub name: String,
+    pub container: BorderSet,
+    pub node: BorderSet,
+    pub sockets: SocketSet,
+    pub wire: WireSet,
+    pub colors: TypeColors,
+    pub mono_sockets: TypeGlyphs,
+    pub selection: [u8; 3],
+}
+impl Default for Theme {
+    fn default() -> Self {
+        Self::unicode()
+    }
+}
+impl Theme {
+    pub fn unicode() -> Self {
+        Self {
+            name: "unicode".to_string(),
+            container: BorderSet { trcorner: '┐', tlcorner: '┌', drcorner: '┘', dlcorner: '└', tedge: '─', dedge: '─', redge: '│', ledge: '│' },
+            node: BorderSet { trcorner: '╗', tlcorner: '╔', drcorner: '╝', dlcorner: '╚', tedge: '─', dedge: '─', redge: '║', ledge: '║' },
+            sockets: SocketSet { input: '╣', output: '╠', reroute: '●' },
+            wire: WireSet {
+                hedge: '─', vedge: '│',
+                trcorner: '┐', tlcorner: '┌', drcorner: '┘', dlcorner: '└',
+                rtee: '┤', ltee: '├', ttee: '┬', dtee: '┴', cross: '┼',
+            },
+            colors: TypeColors { isize: [68, 139, 211], string: [38, 209, 111], any: [160, 160, 160] },
+            mono_sockets: TypeGlyphs { isize: '■', string: '◆', any: '●' },
+            selection: [230, 200, 60],
+        }
+    }
+
+    pub fn double() -> Self {
+        Self {
+            name: "double".to_string(),
+            container: BorderSet { trcorner: '╗', tlcorner: '╔', drcorner: '╝', dlcorner: '╚', tedge: '═', dedge: '═', redge: '║', ledge: '║' },
+            node: BorderSet { trcorner: '╗', tlcorner: '╔', drcorner: '╝', dlcorner: '╚', tedge: '═', dedge: '═', redge: '║', ledge: '║' },
+            sockets: SocketSet { input: '╣', output: '╠', reroute: '●' },
+            wire: WireSet {
+                hedge: '═', vedge: '║',
+                trcorner: '╗', tlcorner: '╔', drcorner: '╝', dlcorner: '╚',
+                rtee: '╣', ltee: '╠', ttee: '╦', dtee: '╩', cross: '╬',
+            },
+            ..Self::unicode()
+        }
+    }
+
+    pub fn rounded() -> Self {
+        Self {
+            name: "rounded".to_string(),
+            container: BorderSet { trcorner: '╮', tlcorner: '╭', drcorner: '╯', dlcorner: '╰', tedge: '─', dedge: '─', redge: '│', ledge: '│' },
+            node: BorderSet { trcorner: '╮', tlcorner: '╭', drcorner: '╯', dlcorner: '╰', tedge: '─', dedge: '─', redge: '│', ledge: '│' },
+            sockets: SocketSet { input: '┤', output: '├', reroute: '●' },
+            wire: WireSet {
+                hedge: '─', vedge: '│',
+                trcorner: '╮', tlcorner: '╭', drcorner: '╯', dlcorner: '╰',
+                rtee: '┤', ltee: '├', ttee: '┬', dtee: '┴', cross: '┼',
+            },
+            ..Self::unicode()
+        }
+    }
+
+    pub fn ascii() -> Self {
+        Self {
+            name: "ascii".to_string(),
+            container: BorderSet { trcorner: '+', tlcorner: '+', drcorner: '+', dlcorner: '+', tedge: '-', dedge: '-', redge: '|', ledge: '|' },
+            node: BorderSet { trcorner: '+', tlcorner: '+', drcorner: '+', dlcorner: '+', tedge: '=', dedge: '=', redge: '|', ledge: '|' },
+            sockets: SocketSet { input: '>', output: '>', reroute: 'o' },
+            wire: WireSet {
+                hedge: '-', vedge: '|',
+                trcorner: '+', tlcorner: '+', drcorner: '+', dlcorner: '+',
+                rtee: '+', ltee: '+', ttee: '+', dtee: '+', cross: '+',
+            },
+            mono_sockets: TypeGlyphs { isize: '#', string: '$', any: 'o' },
+            ..Self::unicode()
+        }
+    }
+
+    pub fn get_builtin(name: &str) -> Option<Theme> {
+        match name {
+            "unicode" => Some(Self::unicode()),
+            "double" => Some(Self::double()),
+            "rounded" => Some(Self::rounded()),
+            "ascii" => Some(Self::ascii()),
+            _ => None,
+        }
+    }
+
+    // missing keys in the file fall back to the unicode theme, also inside a table
+    pub fn load(path: &str) -> Result<Theme, String> {
+        let text = fs::read_to_string(path).map_err(|_| format!("the theme file {} can not be loaded.", path))?;
+        Self::from_text(&text).map_err(|message| format!("the theme file {} is invalid: {}", path, message))
+    }
+
+    pub fn from_text(text: &str) -> Result<Theme, String> {
+        let theme: toml::Table = toml::from_str(text).map_err(|e| e.message().to_string())?;
+        let mut base = toml::Table::try_from(Self::unicode()).map_err(|e| e.to_string())?;
+        merge(&mut base, theme);
+        base.try_into().map_err(|e: toml::de::Error| e.message().to_string())
+    }
+
+    pub fn get_type_color(&self, data_type: MyDataType) -> [u8; 3] {
+        match data_type {
+            MyDataType::Isize => self.colors.isize,
+            MyDataType::String => self.colors.string,
+            MyDataType::Any => self.colors.any,
+        }
+    }
+
+    pub fn get_type_glyph(&self, data_type: MyDataType) -> char {
+        match data_type {
+            MyDataType::Isize => self.mono_sockets.isize,
+            MyDataType::String => self.mono_sockets.string,
+            MyDataType::Any => self.mono_sockets.any,
+        }
+    }
+}